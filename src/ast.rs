@@ -2,20 +2,48 @@
 pub enum Type {
     I32,
     F32,
+    /// An i32 address of a length-prefixed string in linear memory
+    Str,
+    /// `null`, an i32 sentinel that can be stored and passed but not operated on
+    Null,
+    /// An i32 address of a length-prefixed array of i32 elements in linear memory
+    Array,
+}
+
+/// How i32 `+`, `-`, `*` and negation behave on overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticMode {
+    /// Two's-complement wrap, like plain `i32.add`
+    Wrapping,
+    /// Trap at runtime (`--checked-arithmetic`)
+    Checked,
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Number(i32),
     NumberF32(f32),
+    StringLit(String),
     Identifier(String),
+    Null,
     Binary(Box<Expr>, BinOp, Box<Expr>),
     Unary(UnaryOp, Box<Expr>),
     Call(String, Vec<Expr>),
     Logical(Box<Expr>, LogicalOp, Box<Expr>),
+    Update(UpdateOp, String),
+    /// `name = value`, which yields the stored value
+    Assign(String, Box<Expr>),
+    /// `cond ? then : else`
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `[a, b, c]`
+    ArrayLit(Vec<Expr>),
+    /// `array[index]`
+    Index(Box<Expr>, Box<Expr>),
+    /// `object.property`; only an array's `length` exists
+    Member(Box<Expr>, String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BinOp {
     Add,
     Sub,
@@ -28,18 +56,49 @@ pub enum BinOp {
     Gt,
     Le,
     Ge,
+    // Bitwise (i32 only)
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UnaryOp {
     Neg,
     Not,
+    BitNot,
 }
 
-#[derive(Debug, Clone)]
+/// `++` and `--`. Prefix forms yield the updated value, postfix forms the old one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateOp {
+    PreInc,
+    PreDec,
+    PostInc,
+    PostDec,
+}
+
+impl UpdateOp {
+    pub fn is_prefix(self) -> bool {
+        matches!(self, UpdateOp::PreInc | UpdateOp::PreDec)
+    }
+
+    /// The arithmetic applied to the variable, with 1 as the right operand.
+    pub fn bin_op(self) -> BinOp {
+        match self {
+            UpdateOp::PreInc | UpdateOp::PostInc => BinOp::Add,
+            UpdateOp::PreDec | UpdateOp::PostDec => BinOp::Sub,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum LogicalOp {
     And,
     Or,
+    Nullish,
 }
 
 #[derive(Debug, Clone)]
@@ -48,19 +107,52 @@ pub struct Stmt {
     pub line: usize,
 }
 
+impl Stmt {
+    /// Whether a `break` or `continue` in this statement leaves it, i.e. is not
+    /// inside a loop nested within it. A `break` inside a nested `switch` only
+    /// leaves the `switch`.
+    pub fn exits_loop(&self) -> bool {
+        self.jumps_out(true)
+    }
+
+    fn jumps_out(&self, breaks: bool) -> bool {
+        match &self.kind {
+            StmtKind::Break => breaks,
+            StmtKind::Continue => true,
+            StmtKind::If(_, then_branch, else_branch) => {
+                then_branch.jumps_out(breaks)
+                    || else_branch.as_ref().is_some_and(|eb| eb.jumps_out(breaks))
+            }
+            StmtKind::Block(stmts) => stmts.iter().any(|s| s.jumps_out(breaks)),
+            StmtKind::Switch(_, cases, default) => cases
+                .iter()
+                .flat_map(|(_, body)| body)
+                .chain(default.iter().flatten())
+                .any(|s| s.jumps_out(false)),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum StmtKind {
     Let(String, Expr),
-    Const(String, Expr),
+    /// The value is `None` only for `const name;`, which the analyzer rejects
+    Const(String, Option<Expr>),
     Assign(String, Expr),
     If(Expr, Box<Stmt>, Option<Box<Stmt>>),
     While(Expr, Box<Stmt>),
+    /// `do body while (cond);` - the body runs once before the first test
+    DoWhile(Box<Stmt>, Expr),
     For(
         Option<Box<Stmt>>,
         Option<Expr>,
         Option<Box<Stmt>>,
         Box<Stmt>,
     ),
+    /// `switch (disc) { case label: ... default: ... }`. Cases fall through to
+    /// the next one unless they `break`; `default` always comes last.
+    Switch(Expr, Vec<(Expr, Vec<Stmt>)>, Option<Vec<Stmt>>),
     Block(Vec<Stmt>),
     Return(Expr),
     Break,
@@ -76,6 +168,8 @@ pub struct Function {
     pub return_type: Option<Type>,
     pub body: Vec<Stmt>,
     pub line: usize,
+    /// Names of identical functions merged into this one; each is still exported.
+    pub aliases: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -83,3 +177,255 @@ pub struct Program {
     pub functions: Vec<Function>,
     pub top_level: Vec<Stmt>,
 }
+
+/// A function provided by the host through an `env` import. Host functions
+/// return nothing, so they can only be called as statements.
+#[derive(Debug)]
+pub struct HostFunction {
+    /// The name called in source; a program function of the same name takes precedence
+    pub name: &'static str,
+    /// The import's field name, also used as its WAT id
+    pub import: &'static str,
+    pub param: Type,
+}
+
+/// `print` is overloaded on its argument type; each overload is a separate import.
+pub const HOST_FUNCTIONS: &[HostFunction] = &[
+    HostFunction {
+        name: "print",
+        import: "print",
+        param: Type::I32,
+    },
+    HostFunction {
+        name: "print",
+        import: "print_f32",
+        param: Type::F32,
+    },
+    HostFunction {
+        name: "print",
+        import: "print_str",
+        param: Type::Str,
+    },
+];
+
+pub fn is_host_function(name: &str) -> bool {
+    HOST_FUNCTIONS.iter().any(|host| host.name == name)
+}
+
+/// The import a host call resolves to for an argument of type `arg`.
+pub fn host_function(name: &str, arg: Type) -> Option<&'static HostFunction> {
+    HOST_FUNCTIONS
+        .iter()
+        .find(|host| host.name == name && host.param == arg)
+}
+
+impl Program {
+    /// Serializes the AST as JSON for `--emit ast-json`.
+    pub fn to_json(&self) -> String {
+        let functions: Vec<String> = self.functions.iter().map(function_json).collect();
+        format!(
+            "{{\"functions\":[{}],\"top_level\":{}}}",
+            functions.join(","),
+            stmts_json(&self.top_level)
+        )
+    }
+}
+
+fn function_json(func: &Function) -> String {
+    let params: Vec<String> = func.params.iter().map(|p| json_string(p)).collect();
+    let param_types = match &func.param_types {
+        Some(types) => {
+            let types: Vec<String> = types.iter().map(|t| type_json(*t)).collect();
+            format!("[{}]", types.join(","))
+        }
+        None => "null".to_string(),
+    };
+    let return_type = func.return_type.map_or("null".to_string(), type_json);
+    let aliases: Vec<String> = func.aliases.iter().map(|a| json_string(a)).collect();
+    format!(
+        "{{\"name\":{},\"params\":[{}],\"param_types\":{},\"return_type\":{},\"line\":{},\"aliases\":[{}],\"body\":{}}}",
+        json_string(&func.name),
+        params.join(","),
+        param_types,
+        return_type,
+        func.line,
+        aliases.join(","),
+        stmts_json(&func.body)
+    )
+}
+
+fn stmts_json(stmts: &[Stmt]) -> String {
+    let stmts: Vec<String> = stmts.iter().map(stmt_json).collect();
+    format!("[{}]", stmts.join(","))
+}
+
+fn stmt_json(stmt: &Stmt) -> String {
+    let fields = match &stmt.kind {
+        StmtKind::Let(name, expr) => format!(
+            "\"type\":\"Let\",\"name\":{},\"value\":{}",
+            json_string(name),
+            expr_json(expr)
+        ),
+        StmtKind::Const(name, expr) => format!(
+            "\"type\":\"Const\",\"name\":{},\"value\":{}",
+            json_string(name),
+            expr.as_ref().map_or("null".to_string(), expr_json)
+        ),
+        StmtKind::Assign(name, expr) => format!(
+            "\"type\":\"Assign\",\"name\":{},\"value\":{}",
+            json_string(name),
+            expr_json(expr)
+        ),
+        StmtKind::If(cond, then_branch, else_branch) => format!(
+            "\"type\":\"If\",\"cond\":{},\"then\":{},\"else\":{}",
+            expr_json(cond),
+            stmt_json(then_branch),
+            else_branch
+                .as_ref()
+                .map_or("null".to_string(), |s| stmt_json(s))
+        ),
+        StmtKind::While(cond, body) => format!(
+            "\"type\":\"While\",\"cond\":{},\"body\":{}",
+            expr_json(cond),
+            stmt_json(body)
+        ),
+        StmtKind::DoWhile(body, cond) => format!(
+            "\"type\":\"DoWhile\",\"body\":{},\"cond\":{}",
+            stmt_json(body),
+            expr_json(cond)
+        ),
+        StmtKind::For(init, cond, incr, body) => format!(
+            "\"type\":\"For\",\"init\":{},\"cond\":{},\"update\":{},\"body\":{}",
+            init.as_ref().map_or("null".to_string(), |s| stmt_json(s)),
+            cond.as_ref().map_or("null".to_string(), expr_json),
+            incr.as_ref().map_or("null".to_string(), |s| stmt_json(s)),
+            stmt_json(body)
+        ),
+        StmtKind::Switch(disc, cases, default) => {
+            let cases: Vec<String> = cases
+                .iter()
+                .map(|(label, body)| {
+                    format!(
+                        "{{\"label\":{},\"body\":{}}}",
+                        expr_json(label),
+                        stmts_json(body)
+                    )
+                })
+                .collect();
+            format!(
+                "\"type\":\"Switch\",\"discriminant\":{},\"cases\":[{}],\"default\":{}",
+                expr_json(disc),
+                cases.join(","),
+                default.as_deref().map_or("null".to_string(), stmts_json)
+            )
+        }
+        StmtKind::Block(stmts) => format!("\"type\":\"Block\",\"body\":{}", stmts_json(stmts)),
+        StmtKind::Return(expr) => format!("\"type\":\"Return\",\"value\":{}", expr_json(expr)),
+        StmtKind::Break => "\"type\":\"Break\"".to_string(),
+        StmtKind::Continue => "\"type\":\"Continue\"".to_string(),
+        StmtKind::Expr(expr) => format!("\"type\":\"Expr\",\"expr\":{}", expr_json(expr)),
+    };
+    format!("{{{},\"line\":{}}}", fields, stmt.line)
+}
+
+fn expr_json(expr: &Expr) -> String {
+    match expr {
+        Expr::Number(n) => format!("{{\"type\":\"Number\",\"value\":{}}}", n),
+        Expr::NumberF32(f) => {
+            // JSON has no NaN/Infinity; folding can overflow f32 to infinity
+            let value = if f.is_finite() {
+                format!("{:?}", f)
+            } else {
+                "null".to_string()
+            };
+            format!("{{\"type\":\"NumberF32\",\"value\":{}}}", value)
+        }
+        Expr::StringLit(s) => format!("{{\"type\":\"String\",\"value\":{}}}", json_string(s)),
+        Expr::Identifier(name) => {
+            format!("{{\"type\":\"Identifier\",\"name\":{}}}", json_string(name))
+        }
+        Expr::Null => "{\"type\":\"Null\"}".to_string(),
+        Expr::Binary(left, op, right) => format!(
+            "{{\"type\":\"Binary\",\"op\":\"{:?}\",\"left\":{},\"right\":{}}}",
+            op,
+            expr_json(left),
+            expr_json(right)
+        ),
+        Expr::Unary(op, operand) => format!(
+            "{{\"type\":\"Unary\",\"op\":\"{:?}\",\"operand\":{}}}",
+            op,
+            expr_json(operand)
+        ),
+        Expr::Call(name, args) => {
+            let args: Vec<String> = args.iter().map(expr_json).collect();
+            format!(
+                "{{\"type\":\"Call\",\"name\":{},\"args\":[{}]}}",
+                json_string(name),
+                args.join(",")
+            )
+        }
+        Expr::Logical(left, op, right) => format!(
+            "{{\"type\":\"Logical\",\"op\":\"{:?}\",\"left\":{},\"right\":{}}}",
+            op,
+            expr_json(left),
+            expr_json(right)
+        ),
+        Expr::Ternary(cond, then_expr, else_expr) => format!(
+            "{{\"type\":\"Ternary\",\"cond\":{},\"then\":{},\"else\":{}}}",
+            expr_json(cond),
+            expr_json(then_expr),
+            expr_json(else_expr)
+        ),
+        Expr::Update(op, name) => format!(
+            "{{\"type\":\"Update\",\"op\":\"{:?}\",\"name\":{}}}",
+            op,
+            json_string(name)
+        ),
+        Expr::Assign(name, value) => format!(
+            "{{\"type\":\"Assign\",\"name\":{},\"value\":{}}}",
+            json_string(name),
+            expr_json(value)
+        ),
+        Expr::ArrayLit(elements) => {
+            let elements: Vec<String> = elements.iter().map(expr_json).collect();
+            format!(
+                "{{\"type\":\"ArrayLit\",\"elements\":[{}]}}",
+                elements.join(",")
+            )
+        }
+        Expr::Index(array, index) => format!(
+            "{{\"type\":\"Index\",\"array\":{},\"index\":{}}}",
+            expr_json(array),
+            expr_json(index)
+        ),
+        Expr::Member(object, property) => format!(
+            "{{\"type\":\"Member\",\"object\":{},\"property\":{}}}",
+            expr_json(object),
+            json_string(property)
+        ),
+    }
+}
+
+fn type_json(t: Type) -> String {
+    match t {
+        Type::I32 => "\"i32\"".to_string(),
+        Type::F32 => "\"f32\"".to_string(),
+        Type::Str => "\"string\"".to_string(),
+        Type::Null => "\"null\"".to_string(),
+        Type::Array => "\"array\"".to_string(),
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,13 +1,89 @@
 use crate::ast::*;
-use crate::error::Result;
+use crate::error::{
+    CompilerError, Result, E_COMPONENT_EXPORT, E_CONST_INIT, E_DUPLICATE_FUNCTION, E_MODULE_NAME,
+    E_OUTSIDE_LOOP, E_WASI_PRINT,
+};
 use std::collections::HashMap;
 
+// The i32 that stands for `null`. The analyzer keeps null apart from numbers,
+// so it never meets an i32 that happens to hold the same bits.
+const NULL_SENTINEL: i32 = i32::MIN;
+
+// Size of the buffer WASI `print` uses, and the offset of the newline at its end
+const PRINT_BUFFER_SIZE: u32 = 32;
+const PRINT_NEWLINE: u32 = 31;
+
+/// What the top-level code compiles to, from the CLI's `--entry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    /// `_start`, exported and returning the last expression's value. The default.
+    Start,
+    /// A `_start` with no result, run by the module's `(start)` section as WASI expects
+    Wasi,
+    /// `main`, exported and returning the last expression's value
+    Export,
+    /// Nothing; only the functions are compiled
+    None,
+}
+
 pub struct CodeGen {
     output: Vec<String>,
-    function_return_types: HashMap<String, Type>,
+    // None for functions that return nothing
+    function_return_types: HashMap<String, Option<Type>>,
     label_counter: usize,
+    // Innermost `continue` target, and innermost `break` target (a loop or `switch`)
     loop_stack: Vec<usize>,
+    break_stack: Vec<usize>,
     variable_types: HashMap<String, Type>,
+    module_name: Option<String>,
+    component: bool,
+    arithmetic: ArithmeticMode,
+    tail_calls: bool,
+    // `print` writes to stdout through WASI `fd_write` instead of calling `env` imports
+    wasi: bool,
+    entry: Entry,
+    // Interned string literals and their addresses in linear memory
+    strings: Vec<(String, u32)>,
+    data_end: u32,
+    // Whether the program builds arrays, which are bump-allocated after the strings
+    uses_heap: bool,
+    // Address of the buffers WASI `print` formats into, once reserved
+    print_buffer: Option<u32>,
+    // Import names of the host functions the program calls
+    host_imports: Vec<&'static str>,
+    // Temporaries the current function needs beyond `$_result`
+    temps: Vec<(String, Type)>,
+    // Result of the function being generated, which a `return_call` callee must share
+    return_type: Option<Type>,
+    // Type of `$_result` while generating `_start`, where expression statements
+    // of that type store their value in it
+    start_result: Option<Type>,
+}
+
+// What [`CodeGen::gen_func`] needs to know about a function beyond its body
+struct FuncShape<'a> {
+    name: &'a str,
+    exports: Vec<&'a str>,
+    params: Vec<(String, Type)>,
+    // Declaration line, noted after the signature; `_start` has none
+    line: Option<usize>,
+    body: &'a [Stmt],
+    result: ResultStrategy,
+}
+
+// Where a function's result comes from
+#[derive(Clone, Copy)]
+enum ResultStrategy {
+    // `return` statements, of the declared type; None returns nothing
+    Return(Option<Type>),
+    // The value of the last expression statement, kept in `$_result`
+    LastExpr,
+}
+
+impl Default for CodeGen {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CodeGen {
@@ -17,32 +93,365 @@ impl CodeGen {
             function_return_types: HashMap::new(),
             label_counter: 0,
             loop_stack: Vec::new(),
+            break_stack: Vec::new(),
             variable_types: HashMap::new(),
+            module_name: None,
+            component: false,
+            arithmetic: ArithmeticMode::Wrapping,
+            tail_calls: true,
+            wasi: false,
+            entry: Entry::Start,
+            strings: Vec::new(),
+            data_end: 0,
+            uses_heap: false,
+            print_buffer: None,
+            host_imports: Vec::new(),
+            temps: Vec::new(),
+            return_type: None,
+            start_result: None,
         }
     }
 
+    /// Names the module: `(module $name ...)`.
+    pub fn module_name(mut self, name: Option<String>) -> Self {
+        self.module_name = name;
+        self
+    }
+
+    /// Wraps the core module in a component that lifts each function as an export.
+    pub fn component(mut self, component: bool) -> Self {
+        self.component = component;
+        self
+    }
+
+    /// Checked mode traps on i32 `+`, `-`, `*` and negation overflow.
+    pub fn arithmetic(mut self, mode: ArithmeticMode) -> Self {
+        self.arithmetic = mode;
+        self
+    }
+
+    /// Without tail calls, `return f(x)` is a `call` then `return` rather than
+    /// `return_call`, for runtimes that lack the tail-call proposal.
+    pub fn tail_calls(mut self, tail_calls: bool) -> Self {
+        self.tail_calls = tail_calls;
+        self
+    }
+
+    /// Under WASI, `print` formats its argument into linear memory and writes it,
+    /// with a newline, to stdout through `wasi_snapshot_preview1.fd_write`.
+    pub fn wasi(mut self, wasi: bool) -> Self {
+        self.wasi = wasi;
+        self
+    }
+
+    /// Chooses how the top-level code is exposed; see [`Entry`].
+    pub fn entry(mut self, entry: Entry) -> Self {
+        self.entry = entry;
+        self
+    }
+
     pub fn generate(&mut self, program: &Program) -> Result<String> {
-        self.output.push("(module".to_string());
+        if let Some(name) = &self.module_name {
+            if !is_valid_wat_id(name) {
+                return Err(CompilerError::codegen(
+                    0,
+                    format!("Invalid module name '{}': not a valid WAT identifier", name),
+                )
+                .with_code(E_MODULE_NAME));
+            }
+        }
 
-        //Build function return type map from AST
-        for func in &program.functions {
-            let return_type = func.return_type.unwrap_or(Type::I32);
-            self.function_return_types
-                .insert(func.name.clone(), return_type);
+        if self.entry == Entry::Export {
+            if let Some((_, func)) = exported_functions(program)
+                .into_iter()
+                .find(|(name, _)| *name == "main")
+            {
+                return Err(CompilerError::codegen(
+                    func.line,
+                    "Function 'main' clashes with the top-level code, exported as main".to_string(),
+                )
+                .with_code(E_DUPLICATE_FUNCTION));
+            }
+        }
+
+        let export_names = if self.component {
+            component_export_names(program)?
+        } else {
+            Vec::new()
+        };
+
+        let module_start = self.output.len();
+        match (&self.module_name, self.component) {
+            (Some(name), false) => self.output.push(format!("(module ${}", name)),
+            (None, false) => self.output.push("(module".to_string()),
+            (Some(name), true) => self.output.push(format!("(core module ${}", name)),
+            (None, true) => self.output.push("(core module $core".to_string()),
         }
 
+        self.collect_function_return_types(program);
+
         // Generate all functions
         for func in &program.functions {
             self.gen_function(func)?;
         }
 
+        if self.arithmetic == ArithmeticMode::Checked {
+            self.gen_checked_helper();
+        }
+
         // Generate _start function for top-level code
         self.gen_start(&program.top_level)?;
 
+        if self.component && !self.host_imports.is_empty() {
+            return Err(CompilerError::codegen(
+                0,
+                "Host functions need imports, which components do not support yet".to_string(),
+            )
+            .with_code(E_COMPONENT_EXPORT));
+        }
+        if self.wasi && !self.host_imports.is_empty() {
+            self.gen_wasi_print(module_start)?;
+        } else if !self.host_imports.is_empty() {
+            // Imports must precede the module's own functions
+            let imports: Vec<String> = HOST_FUNCTIONS
+                .iter()
+                .filter(|host| self.host_imports.contains(&host.import))
+                .map(|host| {
+                    // A string goes to the host as its bytes' address and length
+                    let params = match host.param {
+                        Type::Str => "i32 i32",
+                        param => type_to_wasm(param),
+                    };
+                    format!(
+                        "  (import \"env\" \"{}\" (func ${} (param {})))",
+                        host.import, host.import, params
+                    )
+                })
+                .collect();
+            self.output
+                .splice(module_start + 1..module_start + 1, imports);
+            if self.host_imports.contains(&"print_str") {
+                self.gen_print_str_helper();
+            }
+        }
+        if self.uses_heap {
+            self.gen_alloc_helper();
+        }
+        self.gen_memory();
+
         self.output.push(")".to_string());
+
+        if self.component {
+            self.wrap_component(program, &export_names);
+        }
+
         Ok(self.output.join("\n"))
     }
 
+    // Nest the core module in a component and lift every function as a component export
+    fn wrap_component(&mut self, program: &Program, export_names: &[String]) {
+        let core_name = self.module_name.as_deref().unwrap_or("core");
+
+        let mut lines = vec!["(component".to_string()];
+        lines.extend(self.output.iter().map(|line| format!("  {}", line)));
+        lines.push(format!(
+            "  (core instance $inst (instantiate ${}))",
+            core_name
+        ));
+
+        let exports = exported_functions(program);
+        for ((name, func), export_name) in exports.into_iter().zip(export_names) {
+            let default_param_types = vec![Type::I32; func.params.len()];
+            let param_types = func.param_types.as_ref().unwrap_or(&default_param_types);
+            let params: Vec<String> = func
+                .params
+                .iter()
+                .zip(param_types)
+                .map(|(p, t)| format!(" (param \"{}\" {})", kebab_case(p), type_to_wit(*t)))
+                .collect();
+            let result = func
+                .return_type
+                .map(|t| format!(" (result {})", type_to_wit(t)))
+                .unwrap_or_default();
+
+            lines.push(format!(
+                "  (func ${}{}{}",
+                wat_id(name),
+                params.concat(),
+                result
+            ));
+            lines.push(format!("    (canon lift (core func $inst \"{}\")))", name));
+            lines.push(format!(
+                "  (export \"{}\" (func ${}))",
+                export_name,
+                wat_id(name)
+            ));
+        }
+
+        lines.push(")".to_string());
+        self.output = lines;
+    }
+
+    /// The WIT world describing the exports of `--component` output.
+    pub fn wit_world(&self, program: &Program) -> Result<String> {
+        let export_names = component_export_names(program)?;
+        let world = self
+            .module_name
+            .as_deref()
+            .map(kebab_case)
+            .unwrap_or_else(|| "module".to_string());
+
+        let mut lines = vec![
+            "package local:compiler;".to_string(),
+            String::new(),
+            format!("world {} {{", wit_ident(&world)),
+        ];
+        for ((_, func), export_name) in exported_functions(program).into_iter().zip(&export_names) {
+            let default_param_types = vec![Type::I32; func.params.len()];
+            let param_types = func.param_types.as_ref().unwrap_or(&default_param_types);
+            let params: Vec<String> = func
+                .params
+                .iter()
+                .zip(param_types)
+                .map(|(p, t)| format!("{}: {}", wit_ident(&kebab_case(p)), type_to_wit(*t)))
+                .collect();
+            let result = func
+                .return_type
+                .map(|t| format!(" -> {}", type_to_wit(t)))
+                .unwrap_or_default();
+            lines.push(format!(
+                "  export {}: func({}){};",
+                wit_ident(export_name),
+                params.join(", "),
+                result
+            ));
+        }
+        lines.push("}".to_string());
+
+        Ok(lines.join("\n"))
+    }
+
+    //Build function return type map from AST
+    fn collect_function_return_types(&mut self, program: &Program) {
+        for func in &program.functions {
+            self.function_return_types
+                .insert(func.name.clone(), func.return_type);
+        }
+    }
+
+    /// Generates the module and counts each instruction mnemonic, most frequent first.
+    pub fn opcode_stats(&mut self, program: &Program) -> Result<String> {
+        self.generate(program)?;
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for line in &self.output {
+            let line = line.trim();
+            // Skip module structure, declarations and comments
+            if line.is_empty()
+                || line.starts_with('(')
+                || line.starts_with(')')
+                || line.starts_with(";;")
+            {
+                continue;
+            }
+            if let Some(mnemonic) = line.split_whitespace().next() {
+                *counts.entry(mnemonic).or_insert(0) += 1;
+            }
+        }
+
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let lines: Vec<String> = counts
+            .iter()
+            .map(|(mnemonic, count)| format!("{:>5} {}", count, mnemonic))
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    /// One `name: (params) -> result` line per exported function, the top-level
+    /// code's export (`_start` or `main`) last.
+    pub fn list_exports(&mut self, program: &Program) -> String {
+        self.collect_function_return_types(program);
+
+        let mut lines = Vec::new();
+        for (name, func) in exported_functions(program) {
+            let default_param_types = vec![Type::I32; func.params.len()];
+            let param_types = func.param_types.as_ref().unwrap_or(&default_param_types);
+            let params: Vec<&str> = param_types.iter().map(|t| type_to_wasm(*t)).collect();
+            // Functions that return nothing show `()`, like a WAT signature without a result
+            let result = func.return_type.map_or("()", type_to_wasm);
+            lines.push(format!("{}: ({}) -> {}", name, params.join(", "), result));
+        }
+
+        self.variable_types.clear();
+        self.collect_variable_types(&program.top_level);
+        let start_return_type = self.infer_start_return_type(&program.top_level);
+        let start_name = match self.entry {
+            Entry::Start => Some("_start"),
+            Entry::Export => Some("main"),
+            Entry::Wasi | Entry::None => None,
+        };
+        if let Some(name) = start_name {
+            lines.push(format!(
+                "{}: () -> {}",
+                name,
+                type_to_wasm(start_return_type)
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Generates the module and reports the deepest operand stack each function reaches.
+    pub fn stack_depths(&mut self, program: &Program) -> Result<String> {
+        // Depth is a property of the core module, so leave out the component wrapper
+        self.component = false;
+        self.generate(program)?;
+
+        // Parameter and result counts of everything a `call` can name
+        let mut signatures: HashMap<String, (usize, usize)> = program
+            .functions
+            .iter()
+            .map(|func| {
+                let results = usize::from(func.return_type.is_some());
+                (wat_id(&func.name), (func.params.len(), results))
+            })
+            .collect();
+        signatures.insert("_checked_i32".to_string(), (1, 1));
+        signatures.insert("_print_str".to_string(), (1, 0));
+        signatures.insert("_alloc".to_string(), (1, 1));
+        signatures.insert("_print_i32".to_string(), (1, 0));
+        signatures.insert("fd_write".to_string(), (4, 1));
+        for host in HOST_FUNCTIONS {
+            let params = if host.param == Type::Str { 2 } else { 1 };
+            signatures.insert(host.import.to_string(), (params, 0));
+        }
+
+        let mut lines = Vec::new();
+        let mut current: Option<(&str, Vec<&str>)> = None;
+        for line in &self.output {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("(func $") {
+                let name = rest.split_whitespace().next().unwrap_or(rest);
+                current = Some((name, Vec::new()));
+            } else if line == ")" {
+                if let Some((name, body)) = current.take() {
+                    lines.push(format!(
+                        "{:>5} {}",
+                        max_stack_depth(&body, &signatures),
+                        name
+                    ));
+                }
+            } else if let Some((_, body)) = &mut current {
+                // Skip declarations and comments
+                if !line.is_empty() && !line.starts_with('(') && !line.starts_with(";;") {
+                    body.push(line);
+                }
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
     fn infer_expr_type_quick(&self, expr: &Expr) -> Type {
         match expr {
             Expr::Number(_) => Type::I32,
@@ -51,10 +460,20 @@ impl CodeGen {
                 let left_type = self.infer_expr_type_quick(left);
                 let right_type = self.infer_expr_type_quick(right);
 
-                // Comparisons return i32
+                // Comparisons and bitwise operators return i32
                 if matches!(
                     op,
-                    BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge
+                    BinOp::Eq
+                        | BinOp::Ne
+                        | BinOp::Lt
+                        | BinOp::Gt
+                        | BinOp::Le
+                        | BinOp::Ge
+                        | BinOp::And
+                        | BinOp::Or
+                        | BinOp::Xor
+                        | BinOp::Shl
+                        | BinOp::Shr
                 ) {
                     return Type::I32;
                 }
@@ -68,7 +487,7 @@ impl CodeGen {
             }
             Expr::Unary(op, operand) => match op {
                 UnaryOp::Neg => self.infer_expr_type_quick(operand),
-                UnaryOp::Not => Type::I32,
+                UnaryOp::Not | UnaryOp::BitNot => Type::I32,
             },
             Expr::Logical(left, _, right) => {
                 let left_type = self.infer_expr_type_quick(left);
@@ -80,7 +499,22 @@ impl CodeGen {
                 }
             }
 
-            Expr::Identifier(name) => {
+            Expr::StringLit(_) => Type::Str,
+            Expr::Null => Type::Null,
+            Expr::ArrayLit(_) => Type::Array,
+            Expr::Index(..) | Expr::Member(..) => Type::I32,
+            Expr::Ternary(_, then_expr, else_expr) => {
+                let then_type = self.infer_expr_type_quick(then_expr);
+                let else_type = self.infer_expr_type_quick(else_expr);
+                if matches!(then_type, Type::Str | Type::Null | Type::Array) {
+                    then_type
+                } else if then_type == Type::F32 || else_type == Type::F32 {
+                    Type::F32
+                } else {
+                    Type::I32
+                }
+            }
+            Expr::Identifier(name) | Expr::Update(_, name) | Expr::Assign(name, _) => {
                 // Look up variable type from the type map
                 self.variable_types.get(name).copied().unwrap_or(Type::I32)
             }
@@ -89,237 +523,647 @@ impl CodeGen {
                 self.function_return_types
                     .get(name)
                     .copied()
+                    .flatten()
                     .unwrap_or(Type::I32)
             }
         }
     }
 
     fn gen_function(&mut self, func: &Function) -> Result<()> {
-        self.variable_types.clear();
-
-        // Collect variable types from statements
-        self.collect_variable_types(&func.body);
-
         // Get types from AST
         let default_param_types = vec![Type::I32; func.params.len()];
         let param_types = func.param_types.as_ref().unwrap_or(&default_param_types);
-        let return_type = func.return_type.unwrap_or(Type::I32);
 
-        // Add param types to variable_types
-        for (param, param_type) in func.params.iter().zip(param_types.iter()) {
+        self.gen_func(FuncShape {
+            name: &func.name,
+            // Merged duplicates are exported from the function that replaced them
+            exports: std::iter::once(&func.name)
+                .chain(&func.aliases)
+                .map(String::as_str)
+                .collect(),
+            params: func
+                .params
+                .iter()
+                .cloned()
+                .zip(param_types.iter().copied())
+                .collect(),
+            line: Some(func.line),
+            body: &func.body,
+            result: ResultStrategy::Return(func.return_type),
+        })
+    }
+
+    fn gen_start(&mut self, stmts: &[Stmt]) -> Result<()> {
+        let (exports, result) = match self.entry {
+            Entry::Start => (vec!["_start"], ResultStrategy::LastExpr),
+            Entry::Export => (vec!["main"], ResultStrategy::LastExpr),
+            // Every expression statement is dropped, as in a function without a result
+            Entry::Wasi => (Vec::new(), ResultStrategy::Return(None)),
+            Entry::None => return Ok(()),
+        };
+        self.gen_func(FuncShape {
+            name: "_start",
+            exports,
+            params: Vec::new(),
+            line: None,
+            body: stmts,
+            result,
+        })?;
+        if self.entry == Entry::Wasi {
+            self.output.push("  (start $_start)".to_string());
+        }
+        Ok(())
+    }
+
+    // The one path from a body to a WAT function, shared by user functions and
+    // `_start`: signature, locals, `$_result` and temporaries, then statements
+    fn gen_func(&mut self, shape: FuncShape) -> Result<()> {
+        self.variable_types.clear();
+
+        // Add param types to variable_types first, since locals may be initialized from them
+        for (param, param_type) in &shape.params {
             self.variable_types.insert(param.clone(), *param_type);
         }
 
-        let locals = self.collect_locals(&func.body, &func.params);
+        // Collect variable types from statements
+        self.collect_variable_types(shape.body);
 
-        // Generate typed parameter declarations
-        let params: Vec<String> = func
-            .params
-            .iter()
-            .zip(param_types.iter())
-            .map(|(p, t)| format!("(param ${} {})", p, type_to_wasm(*t)))
-            .collect();
+        let param_names: Vec<String> = shape.params.iter().map(|(p, _)| p.clone()).collect();
+        let locals = self.collect_locals(shape.body, &param_names);
 
         // Generate typed local declarations
         let local_decls: Vec<String> = locals
             .iter()
-            .map(|l| {
+            .map(|(l, line)| {
                 let var_type = self.variable_types.get(l).copied().unwrap_or(Type::I32);
-                format!("(local ${} {})", l, type_to_wasm(var_type))
+                format!(
+                    "(local ${} {}) ;; line {}",
+                    wat_id(l),
+                    type_to_wasm(var_type),
+                    line
+                )
             })
             .collect();
 
-        self.output.push(format!(
-            "  (func ${} (export \"{}\") {} (result {}) ;; line {}",
-            func.name,
-            func.name,
-            params.join(" "),
-            type_to_wasm(return_type),
-            func.line
-        ));
+        // `_start` returns the last expression's value, inferred once types are known
+        let return_type = match shape.result {
+            ResultStrategy::Return(return_type) => return_type,
+            ResultStrategy::LastExpr => Some(self.infer_start_return_type(shape.body)),
+        };
+        let result = match return_type {
+            Some(t) => format!(" (result {})", type_to_wasm(t)),
+            None => String::new(),
+        };
+        let exports: Vec<String> = shape
+            .exports
+            .iter()
+            .map(|name| format!("(export \"{}\")", name))
+            .collect();
+        let mut header = format!("  (func ${}", wat_id(shape.name));
+        match shape.line {
+            Some(line) => {
+                // Typed parameter declarations; the space before them stays when there are none
+                let params: Vec<String> = shape
+                    .params
+                    .iter()
+                    .map(|(p, t)| format!("(param ${} {})", wat_id(p), type_to_wasm(*t)))
+                    .collect();
+                header.push_str(&format!(
+                    " {} {}{} ;; line {}",
+                    exports.join(" "),
+                    params.join(" "),
+                    result,
+                    line
+                ));
+            }
+            None => {
+                for export in exports {
+                    header.push_str(&format!(" {}", export));
+                }
+                header.push_str(&result);
+            }
+        }
+        self.output.push(header);
 
         for decl in local_decls {
             self.output.push(format!("    {}", decl));
         }
 
         // Add $_result with correct type
-        let result_type = if return_type == Type::F32 {
+        self.output.push("    ;; compiler temporaries".to_string());
+        let result_type = if return_type == Some(Type::F32) {
             "f32"
         } else {
             "i32"
         };
         self.output
             .push(format!("    (local $_result {})", result_type));
+        let temps_at = self.output.len();
 
-        let all_vars: Vec<String> = func.params.iter().chain(locals.iter()).cloned().collect();
-
-        for stmt in &func.body {
-            self.gen_stmt(stmt, &all_vars)?;
+        self.return_type = return_type;
+        self.start_result = match shape.result {
+            ResultStrategy::Return(_) => None,
+            ResultStrategy::LastExpr => return_type,
+        };
+        for stmt in shape.body {
+            self.gen_stmt(stmt)?;
         }
-
-        // Default return value
-        if return_type == Type::F32 {
-            self.output.push("    f32.const 0.0".to_string());
-        } else {
-            self.output.push("    i32.const 0".to_string());
+        self.start_result = None;
+        self.declare_temps(temps_at);
+
+        match shape.result {
+            // Semantic analysis checked that every path returns, so the end of the
+            // body is never reached; it still has to type-check after a loop or `if`
+            ResultStrategy::Return(Some(_)) => {
+                let last = self.output.last().map(|line| line.trim());
+                if !last.is_some_and(|line| line.starts_with("return")) {
+                    self.output.push("    unreachable".to_string());
+                }
+            }
+            ResultStrategy::Return(None) => {}
+            ResultStrategy::LastExpr => self.output.push("    local.get $_result".to_string()),
         }
         self.output.push("  )".to_string());
         Ok(())
     }
 
+    // Walks the same statements as `collect_locals_rec`, so every local it
+    // declares has a type, including those in `else if` chains and bare bodies
     fn collect_variable_types(&mut self, stmts: &[Stmt]) {
         for stmt in stmts {
             match &stmt.kind {
-                StmtKind::Let(name, expr) | StmtKind::Const(name, expr) => {
+                StmtKind::Let(name, expr) | StmtKind::Const(name, Some(expr)) => {
                     let expr_type = self.infer_expr_type_quick(expr);
                     self.variable_types.insert(name.clone(), expr_type);
                 }
                 StmtKind::Block(inner) => self.collect_variable_types(inner),
                 StmtKind::If(_, then_branch, else_branch) => {
-                    if let StmtKind::Block(stmts) = &then_branch.kind {
-                        self.collect_variable_types(stmts);
-                    }
+                    self.collect_variable_types(std::slice::from_ref(then_branch));
                     if let Some(eb) = else_branch {
-                        if let StmtKind::Block(stmts) = &eb.kind {
-                            self.collect_variable_types(stmts);
-                        }
+                        self.collect_variable_types(std::slice::from_ref(eb));
                     }
                 }
-                StmtKind::While(_, body) => {
-                    if let StmtKind::Block(stmts) = &body.kind {
-                        self.collect_variable_types(stmts);
+                StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => {
+                    self.collect_variable_types(std::slice::from_ref(body));
+                }
+                StmtKind::Switch(_, cases, default) => {
+                    for body in cases.iter().map(|(_, body)| body).chain(default) {
+                        self.collect_variable_types(body);
                     }
                 }
                 StmtKind::For(init, _, _, body) => {
-                    // Collect types from init statement
                     if let Some(init_stmt) = init {
-                        match &init_stmt.kind {
-                            StmtKind::Let(name, expr) | StmtKind::Const(name, expr) => {
-                                let expr_type = self.infer_expr_type_quick(expr);
-                                self.variable_types.insert(name.clone(), expr_type);
-                            }
-                            _ => {}
-                        }
-                    }
-                    if let StmtKind::Block(stmts) = &body.kind {
-                        self.collect_variable_types(stmts);
+                        self.collect_variable_types(std::slice::from_ref(init_stmt));
                     }
+                    self.collect_variable_types(std::slice::from_ref(body));
                 }
                 _ => {}
             }
         }
     }
 
-    fn gen_start(&mut self, stmts: &[Stmt]) -> Result<()> {
-        self.variable_types.clear();
-        self.collect_variable_types(stmts);
-
-        let locals = self.collect_locals(stmts, &[]);
-
-        // Generate typed local declarations
-        let local_decls: Vec<String> = locals
-            .iter()
-            .map(|l| {
-                let var_type = self.variable_types.get(l).copied().unwrap_or(Type::I32);
-                format!("(local ${} {})", l, type_to_wasm(var_type))
-            })
-            .collect();
-
-        // Infer _start return type from last expression
-        let start_return_type = self.infer_start_return_type(stmts);
-
-        self.output.push(format!(
-            "  (func $_start (export \"_start\") (result {})",
-            type_to_wasm(start_return_type)
-        ));
-
-        for decl in local_decls {
-            self.output.push(format!("    {}", decl));
-        }
-
-        // Track the last expression value with correct type
-        self.output.push(format!(
-            "    (local $_result {})",
-            type_to_wasm(start_return_type)
-        ));
-
-        for stmt in stmts {
-            self.gen_stmt_with_result(stmt, &locals)?;
-        }
-
-        self.output.push("    local.get $_result".to_string());
-        self.output.push("  )".to_string());
-        Ok(())
+    // The type of the expression statement that ends the top-level code, looking
+    // through blocks and into `if` branches, then-branch first
+    fn infer_start_return_type(&self, stmts: &[Stmt]) -> Type {
+        self.tail_expr_type(stmts).unwrap_or(Type::I32)
     }
 
-    fn infer_start_return_type(&self, stmts: &[Stmt]) -> Type {
-        if let Some(last) = stmts.last() {
-            if let StmtKind::Expr(expr) = &last.kind {
-                return self.infer_expr_type_quick(expr);
-            }
+    fn tail_expr_type(&self, stmts: &[Stmt]) -> Option<Type> {
+        match &stmts.last()?.kind {
+            StmtKind::Expr(expr) => Some(self.infer_expr_type_quick(expr)),
+            StmtKind::Block(inner) => self.tail_expr_type(inner),
+            StmtKind::If(_, then_branch, else_branch) => self
+                .tail_expr_type(std::slice::from_ref(then_branch))
+                .or_else(|| {
+                    else_branch
+                        .as_ref()
+                        .and_then(|eb| self.tail_expr_type(std::slice::from_ref(eb)))
+                }),
+            _ => None,
         }
-        Type::I32
     }
 
-    fn collect_locals(&self, stmts: &[Stmt], exclude: &[String]) -> Vec<String> {
+    // Locals in source order, paired with their declaration line
+    fn collect_locals(&self, stmts: &[Stmt], exclude: &[String]) -> Vec<(String, usize)> {
         let mut locals = Vec::new();
         self.collect_locals_rec(stmts, &mut locals);
         locals
             .into_iter()
-            .filter(|l| !exclude.contains(l))
+            .filter(|(l, _)| !exclude.contains(l))
             .collect()
     }
 
-    fn collect_locals_rec(&self, stmts: &[Stmt], locals: &mut Vec<String>) {
+    fn collect_locals_rec(&self, stmts: &[Stmt], locals: &mut Vec<(String, usize)>) {
         for stmt in stmts {
             match &stmt.kind {
-                StmtKind::Let(name, _) | StmtKind::Const(name, _) => {
-                    if !locals.contains(name) {
-                        locals.push(name.clone());
-                    }
+                StmtKind::Let(name, _) | StmtKind::Const(name, _)
+                    if !locals.iter().any(|(l, _)| l == name) =>
+                {
+                    locals.push((name.clone(), stmt.line));
                 }
                 StmtKind::Block(inner) => self.collect_locals_rec(inner, locals),
                 StmtKind::If(_, then_branch, else_branch) => {
-                    self.collect_locals_rec(&[*then_branch.clone()], locals);
+                    self.collect_locals_rec(std::slice::from_ref(then_branch), locals);
                     if let Some(eb) = else_branch {
-                        self.collect_locals_rec(&[*eb.clone()], locals);
+                        self.collect_locals_rec(std::slice::from_ref(eb), locals);
                     }
                 }
-                StmtKind::While(_, body) => {
-                    self.collect_locals_rec(&[*body.clone()], locals);
+                StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => {
+                    self.collect_locals_rec(std::slice::from_ref(body), locals);
                 }
-                StmtKind::For(init, _, _, body) => {
+                StmtKind::Switch(_, cases, default) => {
+                    for body in cases.iter().map(|(_, body)| body).chain(default) {
+                        self.collect_locals_rec(body, locals);
+                    }
+                }
+                StmtKind::For(init, _, incr, body) => {
                     if let Some(init_stmt) = init {
-                        self.collect_locals_rec(&[*init_stmt.clone()], locals);
+                        self.collect_locals_rec(std::slice::from_ref(init_stmt), locals);
+                    }
+                    if let Some(incr_stmt) = incr {
+                        self.collect_locals_rec(std::slice::from_ref(incr_stmt), locals);
                     }
-                    self.collect_locals_rec(&[*body.clone()], locals);
+                    self.collect_locals_rec(std::slice::from_ref(body), locals);
                 }
                 _ => {}
             }
         }
     }
 
+    // i32 arithmetic done exactly in i64, then narrowed by $_checked_i32
+    fn gen_checked_arith(&mut self, left: &Expr, op: &BinOp, right: &Expr) {
+        self.gen_expr(left);
+        self.output.push("    i64.extend_i32_s".to_string());
+        self.gen_expr(right);
+        self.output.push("    i64.extend_i32_s".to_string());
+        let instr = match op {
+            BinOp::Add => "i64.add",
+            BinOp::Sub => "i64.sub",
+            _ => "i64.mul",
+        };
+        self.output.push(format!("    {}", instr));
+        self.output.push("    call $_checked_i32".to_string());
+    }
+
+    // WASM has no float remainder, so `a % b` is `a - b * trunc(a / b)` like JS,
+    // with each operand evaluated once into a temporary
+    fn gen_f32_rem(&mut self, left: &Expr, right: &Expr) {
+        let id = self.label_counter;
+        self.label_counter += 1;
+        let (a, b) = (format!("_rem_a_{}", id), format!("_rem_b_{}", id));
+        for (operand, temp) in [(left, &a), (right, &b)] {
+            self.gen_expr(operand);
+            if self.infer_expr_type_quick(operand) == Type::I32 {
+                self.output.push("    f32.convert_i32_s".to_string());
+            }
+            self.output.push(format!("    local.set ${}", temp));
+            self.temps.push((temp.clone(), Type::F32));
+        }
+        for line in [
+            format!("    local.get ${}", a),
+            format!("    local.get ${}", b),
+            format!("    local.get ${}", a),
+            format!("    local.get ${}", b),
+            "    f32.div".to_string(),
+            "    f32.trunc".to_string(),
+            "    f32.mul".to_string(),
+            "    f32.sub".to_string(),
+        ] {
+            self.output.push(line);
+        }
+    }
+
+    // Address of a string literal, laid out on first use as a 4-byte little-endian
+    // length followed by the UTF-8 bytes, padded so the next length stays aligned
+    fn string_addr(&mut self, s: &str) -> u32 {
+        if let Some((_, addr)) = self.strings.iter().find(|(lit, _)| lit == s) {
+            return *addr;
+        }
+        let addr = self.data_end;
+        self.strings.push((s.to_string(), addr));
+        self.data_end += (4 + s.len() as u32).next_multiple_of(4);
+        addr
+    }
+
+    // Strings are laid out from address 0; the heap starts where they end
+    fn gen_memory(&mut self) {
+        if self.strings.is_empty() && !self.uses_heap && self.print_buffer.is_none() {
+            return;
+        }
+        let pages = self.data_end.div_ceil(65536).max(1);
+        self.output
+            .push(format!("  (memory (export \"memory\") {})", pages));
+        if self.uses_heap {
+            self.output.push(format!(
+                "  (global $heap_ptr (mut i32) (i32.const {}))",
+                self.data_end
+            ));
+        }
+        for (s, addr) in &self.strings {
+            let mut bytes = (s.len() as u32).to_le_bytes().to_vec();
+            bytes.extend_from_slice(s.as_bytes());
+            self.output.push(format!(
+                "  (data (i32.const {}) \"{}\")",
+                addr,
+                wat_string(&bytes)
+            ));
+        }
+        if let Some(buffer) = self.print_buffer {
+            self.output.push(format!(
+                "  (data (i32.const {}) \"\\0a\")",
+                buffer + PRINT_NEWLINE
+            ));
+        }
+    }
+
+    // Passes a string's bytes and length to the host
+    fn gen_print_str_helper(&mut self) {
+        for line in [
+            "  (func $_print_str (param $str i32)",
+            "    local.get $str",
+            "    i32.const 4",
+            "    i32.add",
+            "    local.get $str",
+            "    i32.load",
+            "    call $print_str",
+            "  )",
+        ] {
+            self.output.push(line.to_string());
+        }
+    }
+
+    // Imports `fd_write` and defines the helpers `print` calls under --wasi. They
+    // share a buffer after the string data: two iovecs, the count `fd_write`
+    // reports, then room for an i32's digits ending in a newline that stays put.
+    fn gen_wasi_print(&mut self, module_start: usize) -> Result<()> {
+        if self.host_imports.contains(&"print_f32") {
+            return Err(CompilerError::codegen(
+                0,
+                "print of an f32 is not supported under --wasi yet".to_string(),
+            )
+            .with_code(E_WASI_PRINT));
+        }
+        let buffer = self.data_end;
+        self.data_end += PRINT_BUFFER_SIZE;
+        self.print_buffer = Some(buffer);
+        let (iovs, written) = (buffer, buffer + 16);
+        let newline = buffer + PRINT_NEWLINE;
+
+        self.output.insert(
+            module_start + 1,
+            "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))"
+                .to_string(),
+        );
+        let write = |count: u32| {
+            [
+                "    i32.const 1".to_string(),
+                format!("    i32.const {}", iovs),
+                format!("    i32.const {}", count),
+                format!("    i32.const {}", written),
+                "    call $fd_write".to_string(),
+                "    drop".to_string(),
+            ]
+        };
+
+        if self.host_imports.contains(&"print") {
+            // Digits are written backwards from the newline, then a `-` if negative.
+            // The magnitude is unsigned, so i32::MIN needs no special case.
+            let mut lines = vec![
+                "  (func $_print_i32 (param $n i32)".to_string(),
+                "    (local $pos i32)".to_string(),
+                "    (local $abs i32)".to_string(),
+                format!("    i32.const {}", newline),
+                "    local.set $pos".to_string(),
+                "    i32.const 0".to_string(),
+                "    local.get $n".to_string(),
+                "    i32.sub".to_string(),
+                "    local.get $n".to_string(),
+                "    local.get $n".to_string(),
+                "    i32.const 0".to_string(),
+                "    i32.lt_s".to_string(),
+                "    select".to_string(),
+                "    local.set $abs".to_string(),
+                "    loop $digits".to_string(),
+                "      local.get $pos".to_string(),
+                "      i32.const 1".to_string(),
+                "      i32.sub".to_string(),
+                "      local.tee $pos".to_string(),
+                "      local.get $abs".to_string(),
+                "      i32.const 10".to_string(),
+                "      i32.rem_u".to_string(),
+                "      i32.const 48".to_string(),
+                "      i32.add".to_string(),
+                "      i32.store8".to_string(),
+                "      local.get $abs".to_string(),
+                "      i32.const 10".to_string(),
+                "      i32.div_u".to_string(),
+                "      local.tee $abs".to_string(),
+                "      br_if $digits".to_string(),
+                "    end".to_string(),
+                "    local.get $n".to_string(),
+                "    i32.const 0".to_string(),
+                "    i32.lt_s".to_string(),
+                "    if".to_string(),
+                "      local.get $pos".to_string(),
+                "      i32.const 1".to_string(),
+                "      i32.sub".to_string(),
+                "      local.tee $pos".to_string(),
+                "      i32.const 45".to_string(),
+                "      i32.store8".to_string(),
+                "    end".to_string(),
+                format!("    i32.const {}", iovs),
+                "    local.get $pos".to_string(),
+                "    i32.store".to_string(),
+                format!("    i32.const {}", iovs),
+                format!("    i32.const {}", newline + 1),
+                "    local.get $pos".to_string(),
+                "    i32.sub".to_string(),
+                "    i32.store offset=4".to_string(),
+            ];
+            lines.extend(write(1));
+            lines.push("  )".to_string());
+            self.output.extend(lines);
+        }
+
+        if self.host_imports.contains(&"print_str") {
+            // The string's bytes, then the newline
+            let mut lines = vec![
+                "  (func $_print_str (param $str i32)".to_string(),
+                format!("    i32.const {}", iovs),
+                "    local.get $str".to_string(),
+                "    i32.const 4".to_string(),
+                "    i32.add".to_string(),
+                "    i32.store".to_string(),
+                format!("    i32.const {}", iovs),
+                "    local.get $str".to_string(),
+                "    i32.load".to_string(),
+                "    i32.store offset=4".to_string(),
+                format!("    i32.const {}", iovs),
+                format!("    i32.const {}", newline),
+                "    i32.store offset=8".to_string(),
+                format!("    i32.const {}", iovs),
+                "    i32.const 1".to_string(),
+                "    i32.store offset=12".to_string(),
+            ];
+            lines.extend(write(2));
+            lines.push("  )".to_string());
+            self.output.extend(lines);
+        }
+        Ok(())
+    }
+
+    // Bumps $heap_ptr by `size` bytes and returns the old value, growing memory
+    // by whole pages when the new end is past it. Nothing is ever freed.
+    fn gen_alloc_helper(&mut self) {
+        for line in [
+            "  (func $_alloc (param $size i32) (result i32)",
+            "    (local $addr i32)",
+            "    global.get $heap_ptr",
+            "    local.set $addr",
+            "    global.get $heap_ptr",
+            "    local.get $size",
+            "    i32.add",
+            "    global.set $heap_ptr",
+            "    global.get $heap_ptr",
+            "    memory.size",
+            "    i32.const 16",
+            "    i32.shl",
+            "    i32.gt_u",
+            "    if",
+            "      global.get $heap_ptr",
+            "      memory.size",
+            "      i32.const 16",
+            "      i32.shl",
+            "      i32.sub",
+            "      i32.const 65535",
+            "      i32.add",
+            "      i32.const 16",
+            "      i32.shr_u",
+            "      memory.grow",
+            "      drop",
+            "    end",
+            "    local.get $addr",
+            "  )",
+        ] {
+            self.output.push(line.to_string());
+        }
+    }
+
+    // An array is its length followed by the elements, one i32 word each
+    fn gen_array_lit(&mut self, elements: &[Expr]) {
+        self.uses_heap = true;
+        let arr = self.array_temp("_arr");
+        self.output
+            .push(format!("    i32.const {}", 4 * (elements.len() + 1)));
+        self.output.push("    call $_alloc".to_string());
+        self.output.push(format!("    local.tee ${}", arr));
+        self.output
+            .push(format!("    i32.const {}", elements.len()));
+        self.output.push("    i32.store".to_string());
+        for (i, element) in elements.iter().enumerate() {
+            self.output.push(format!("    local.get ${}", arr));
+            self.gen_expr(element);
+            self.output
+                .push(format!("    i32.store offset={}", 4 * (i + 1)));
+        }
+        self.output.push(format!("    local.get ${}", arr));
+    }
+
+    // An index outside 0..length traps; a negative index is a huge unsigned one
+    fn gen_index(&mut self, array: &Expr, index: &Expr) {
+        let arr = self.array_temp("_arr");
+        let idx = self.array_temp("_idx");
+        self.gen_expr(array);
+        self.output.push(format!("    local.set ${}", arr));
+        self.gen_expr(index);
+        self.output.push(format!("    local.tee ${}", idx));
+        for line in [
+            format!("    local.get ${}", arr),
+            "    i32.load".to_string(),
+            "    i32.ge_u".to_string(),
+            "    if".to_string(),
+            "      unreachable".to_string(),
+            "    end".to_string(),
+            format!("    local.get ${}", arr),
+            format!("    local.get ${}", idx),
+            "    i32.const 2".to_string(),
+            "    i32.shl".to_string(),
+            "    i32.add".to_string(),
+            "    i32.load offset=4".to_string(),
+        ] {
+            self.output.push(line);
+        }
+    }
+
+    // A fresh i32 temporary for an array address or index
+    fn array_temp(&mut self, prefix: &str) -> String {
+        let temp = format!("{}_{}", prefix, self.label_counter);
+        self.label_counter += 1;
+        self.temps.push((temp.clone(), Type::I32));
+        temp
+    }
+
+    // Narrows an exact i64 result back to i32, trapping if it doesn't fit
+    fn gen_checked_helper(&mut self) {
+        for line in [
+            "  (func $_checked_i32 (param $wide i64) (result i32)",
+            "    local.get $wide",
+            "    local.get $wide",
+            "    i32.wrap_i64",
+            "    i64.extend_i32_s",
+            "    i64.ne",
+            "    if",
+            "    unreachable",
+            "    end",
+            "    local.get $wide",
+            "    i32.wrap_i64",
+            "  )",
+        ] {
+            self.output.push(line.to_string());
+        }
+    }
+
+    // Declares the temporaries the body just generated used, at output index `at`
+    fn declare_temps(&mut self, at: usize) {
+        let decls: Vec<String> = self
+            .temps
+            .drain(..)
+            .map(|(name, t)| format!("    (local ${} {})", name, type_to_wasm(t)))
+            .collect();
+        self.output.splice(at..at, decls);
+    }
+
     fn emit_line_comment(&mut self, line: usize) {
         self.output.push(format!("    ;; line {}", line));
     }
 
-    fn gen_stmt(&mut self, stmt: &Stmt, vars: &[String]) -> Result<()> {
+    fn gen_stmt(&mut self, stmt: &Stmt) -> Result<()> {
         self.emit_line_comment(stmt.line);
         match &stmt.kind {
-            StmtKind::Let(name, expr) => {
-                self.gen_expr(expr, vars);
-                self.output.push(format!("    local.set ${}", name));
+            StmtKind::Let(name, expr) | StmtKind::Const(name, Some(expr)) => {
+                self.gen_expr(expr);
+                self.output.push(format!("    local.set ${}", wat_id(name)));
             }
-            StmtKind::Const(name, expr) => {
-                self.gen_expr(expr, vars);
-                self.output.push(format!("    local.set ${}", name));
+            StmtKind::Const(name, None) => {
+                return Err(CompilerError::codegen(
+                    stmt.line,
+                    format!("Missing initializer in const declaration '{}'", name),
+                )
+                .with_code(E_CONST_INIT));
             }
             StmtKind::Assign(name, expr) => {
-                self.gen_expr(expr, vars);
-                self.output.push(format!("    local.set ${}", name));
+                self.gen_expr(expr);
+                self.output.push(format!("    local.set ${}", wat_id(name)));
+            }
+            StmtKind::If(
+                cond @ Expr::Logical(_, LogicalOp::And | LogicalOp::Or, _),
+                then_branch,
+                else_branch,
+            ) => {
+                self.gen_short_circuit_if(cond, then_branch, else_branch.as_deref())?;
             }
             StmtKind::If(cond, then_branch, else_branch) => {
-                self.gen_expr(cond, vars);
+                self.gen_expr(cond);
                 // Convert f32 to i32 for condition check
                 let cond_type = self.infer_expr_type_quick(cond);
                 if cond_type == Type::F32 {
@@ -329,13 +1173,13 @@ impl CodeGen {
 
                 if else_branch.is_some() {
                     self.output.push("    if".to_string());
-                    self.gen_stmt(then_branch, vars)?;
+                    self.gen_stmt(then_branch)?;
                     self.output.push("    else".to_string());
-                    self.gen_stmt(else_branch.as_ref().unwrap(), vars)?;
+                    self.gen_stmt(else_branch.as_ref().unwrap())?;
                     self.output.push("    end".to_string());
                 } else {
                     self.output.push("    if".to_string());
-                    self.gen_stmt(then_branch, vars)?;
+                    self.gen_stmt(then_branch)?;
                     self.output.push("    end".to_string());
                 }
             }
@@ -343,10 +1187,11 @@ impl CodeGen {
                 let id = self.label_counter;
                 self.label_counter += 1;
                 self.loop_stack.push(id);
+                self.break_stack.push(id);
 
                 self.output.push(format!("    block $break_{}", id));
                 self.output.push(format!("    loop $continue_{}", id));
-                self.gen_expr(cond, vars);
+                self.gen_expr(cond);
 
                 // Convert f32 to boolean for condition
                 let cond_type = self.infer_expr_type_quick(cond);
@@ -358,27 +1203,55 @@ impl CodeGen {
                 }
 
                 self.output.push(format!("    br_if $break_{}", id));
-                self.gen_stmt(body, vars)?;
+                self.gen_stmt(body)?;
                 self.output.push(format!("    br $continue_{}", id));
                 self.output.push("    end".to_string());
                 self.output.push("    end".to_string());
 
                 self.loop_stack.pop();
+                self.break_stack.pop();
+            }
+            StmtKind::DoWhile(body, cond) => {
+                let id = self.label_counter;
+                self.label_counter += 1;
+                self.loop_stack.push(id);
+                self.break_stack.push(id);
+
+                self.output.push(format!("    block $break_{}", id));
+                self.output.push(format!("    loop $loop_{}", id));
+
+                // The body comes first; `continue` exits this block to reach the test
+                self.output.push(format!("    block $continue_{}", id));
+                self.gen_stmt(body)?;
+                self.output.push("    end".to_string());
+
+                self.gen_expr(cond);
+                if self.infer_expr_type_quick(cond) == Type::F32 {
+                    self.output.push("    f32.const 0.0".to_string());
+                    self.output.push("    f32.ne".to_string());
+                }
+                self.output.push(format!("    br_if $loop_{}", id));
+                self.output.push("    end".to_string());
+                self.output.push("    end".to_string());
+
+                self.loop_stack.pop();
+                self.break_stack.pop();
             }
             StmtKind::For(init, cond, incr, body) => {
                 if let Some(init_stmt) = init {
-                    self.gen_stmt(init_stmt, vars)?;
+                    self.gen_stmt(init_stmt)?;
                 }
 
                 let id = self.label_counter;
                 self.label_counter += 1;
                 self.loop_stack.push(id);
+                self.break_stack.push(id);
 
                 self.output.push(format!("    block $break_{}", id));
                 self.output.push(format!("    loop $loop_{}", id));
 
                 if let Some(cond_expr) = cond {
-                    self.gen_expr(cond_expr, vars);
+                    self.gen_expr(cond_expr);
 
                     // Convert f32 to boolean
                     let cond_type = self.infer_expr_type_quick(cond_expr);
@@ -392,12 +1265,13 @@ impl CodeGen {
                     self.output.push(format!("    br_if $break_{}", id));
                 }
 
+                // `continue` exits this block, so it still falls through to the increment
                 self.output.push(format!("    block $continue_{}", id));
-                self.gen_stmt(body, vars)?;
+                self.gen_stmt(body)?;
                 self.output.push("    end".to_string());
 
                 if let Some(incr_stmt) = incr {
-                    self.gen_stmt(incr_stmt, vars)?;
+                    self.gen_stmt(incr_stmt)?;
                 }
 
                 self.output.push(format!("    br $loop_{}", id));
@@ -405,55 +1279,240 @@ impl CodeGen {
                 self.output.push("    end".to_string());
 
                 self.loop_stack.pop();
+                self.break_stack.pop();
+            }
+            StmtKind::Switch(disc, cases, default) => {
+                let id = self.label_counter;
+                self.label_counter += 1;
+                self.break_stack.push(id);
+
+                // The discriminant is evaluated once, before any label
+                let disc_type = self.infer_expr_type_quick(disc);
+                let temp = format!("_switch_{}", id);
+                self.temps.push((temp.clone(), disc_type));
+                self.variable_types.insert(temp.clone(), disc_type);
+                self.gen_expr(disc);
+                self.output.push(format!("    local.set ${}", temp));
+
+                // One block per clause, the first innermost. Leaving a clause's
+                // block starts its body, which falls through into the next body.
+                self.output.push(format!("    block $break_{}", id));
+                let clauses = cases.len() + usize::from(default.is_some());
+                for i in (0..clauses).rev() {
+                    self.output.push(format!("    block $case_{}_{}", id, i));
+                }
+                for (i, (label, _)) in cases.iter().enumerate() {
+                    let test = Expr::Binary(
+                        Box::new(Expr::Identifier(temp.clone())),
+                        BinOp::Eq,
+                        Box::new(label.clone()),
+                    );
+                    self.gen_expr(&test);
+                    self.output.push(format!("    br_if $case_{}_{}", id, i));
+                }
+                // No label matched
+                if default.is_some() {
+                    self.output
+                        .push(format!("    br $case_{}_{}", id, cases.len()));
+                } else {
+                    self.output.push(format!("    br $break_{}", id));
+                }
+                for body in cases.iter().map(|(_, body)| body).chain(default) {
+                    self.output.push("    end".to_string());
+                    for s in body {
+                        self.gen_stmt(s)?;
+                    }
+                }
+                self.output.push("    end".to_string());
+
+                self.break_stack.pop();
             }
             StmtKind::Block(stmts) => {
                 for s in stmts {
-                    self.gen_stmt(s, vars)?;
+                    self.gen_stmt(s)?;
                 }
             }
             StmtKind::Return(expr) => {
-                if let Expr::Call(name, args) = expr {
+                match expr {
                     // Tail call
-                    for arg in args {
-                        self.gen_expr(arg, vars);
+                    Expr::Call(name, args) if self.tail_calls && self.can_tail_call(name) => {
+                        for arg in args {
+                            self.gen_expr(arg);
+                        }
+                        self.output
+                            .push(format!("    return_call ${}", wat_id(name)));
+                    }
+                    _ => {
+                        self.gen_expr(expr);
+                        self.output.push("    return".to_string());
                     }
-                    self.output.push(format!("    return_call ${}", name));
-                } else {
-                    self.gen_expr(expr, vars);
-                    self.output.push("    return".to_string());
                 }
             }
             StmtKind::Break => {
-                if let Some(&loop_id) = self.loop_stack.last() {
-                    self.output.push(format!("    br $break_{}", loop_id));
-                }
+                let loop_id = self.break_stack.last().copied().ok_or_else(|| {
+                    CompilerError::codegen(
+                        stmt.line,
+                        "Break statement outside of loop or switch".to_string(),
+                    )
+                    .with_code(E_OUTSIDE_LOOP)
+                })?;
+                self.output.push(format!("    br $break_{}", loop_id));
             }
             StmtKind::Continue => {
-                if let Some(&loop_id) = self.loop_stack.last() {
-                    self.output.push(format!("    br $continue_{}", loop_id));
-                }
+                let loop_id = self.loop_stack.last().copied().ok_or_else(|| {
+                    CompilerError::codegen(
+                        stmt.line,
+                        "Continue statement outside of loop".to_string(),
+                    )
+                    .with_code(E_OUTSIDE_LOOP)
+                })?;
+                self.output.push(format!("    br $continue_{}", loop_id));
+            }
+            // Host functions and functions without a `return` leave no result to drop
+            StmtKind::Expr(Expr::Call(name, args)) if self.is_host_call(name) => {
+                self.gen_host_call(name, args);
             }
+            StmtKind::Expr(expr @ Expr::Call(name, _)) if self.is_void_call(name) => {
+                self.gen_expr(expr);
+            }
+            // In `_start`, the value of each expression statement that matches its
+            // result type replaces the last one in $_result
             StmtKind::Expr(expr) => {
-                self.gen_expr(expr, vars);
-                self.output.push("    drop".to_string());
+                self.gen_expr(expr);
+                if self.start_result == Some(self.infer_expr_type_quick(expr)) {
+                    self.output.push("    local.set $_result".to_string());
+                } else {
+                    self.output.push("    drop".to_string());
+                }
             }
         }
         Ok(())
     }
 
-    fn gen_stmt_with_result(&mut self, stmt: &Stmt, vars: &[String]) -> Result<()> {
-        self.emit_line_comment(stmt.line);
-        match &stmt.kind {
-            StmtKind::Expr(expr) => {
-                self.gen_expr(expr, vars);
-                self.output.push("    local.set $_result".to_string());
+    // `if (a && b)` / `if (a || b)` branches on each operand in turn instead of
+    // computing the combined value: fall into the then-branch, or jump past it
+    fn gen_short_circuit_if(
+        &mut self,
+        cond: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<()> {
+        let id = self.label_counter;
+        self.label_counter += 1;
+
+        self.output.push(format!("    block $if_{}", id));
+        match else_branch {
+            Some(else_branch) => {
+                self.output.push(format!("    block $else_{}", id));
+                self.gen_branch_if_false(cond, &format!("$else_{}", id));
+                self.gen_stmt(then_branch)?;
+                self.output.push(format!("    br $if_{}", id));
+                self.output.push("    end".to_string());
+                self.gen_stmt(else_branch)?;
+            }
+            None => {
+                self.gen_branch_if_false(cond, &format!("$if_{}", id));
+                self.gen_stmt(then_branch)?;
             }
-            _ => self.gen_stmt(stmt, vars)?,
         }
+        self.output.push("    end".to_string());
         Ok(())
     }
 
-    fn gen_expr(&mut self, expr: &Expr, vars: &[String]) {
+    // Jumps to `label` when `cond` is falsy; falls through when it is truthy
+    fn gen_branch_if_false(&mut self, cond: &Expr, label: &str) {
+        match cond {
+            Expr::Logical(left, LogicalOp::And, right) => {
+                self.gen_branch_if_false(left, label);
+                self.gen_branch_if_false(right, label);
+            }
+            Expr::Logical(left, LogicalOp::Or, right) => {
+                let id = self.label_counter;
+                self.label_counter += 1;
+                let truthy = format!("$or_{}", id);
+
+                self.output.push(format!("    block {}", truthy));
+                self.gen_branch_if_true(left, &truthy);
+                self.gen_branch_if_false(right, label);
+                self.output.push("    end".to_string());
+            }
+            Expr::Unary(UnaryOp::Not, operand) => self.gen_branch_if_true(operand, label),
+            _ => {
+                self.gen_expr(cond);
+                if self.infer_expr_type_quick(cond) == Type::F32 {
+                    self.output.push("    f32.const 0.0".to_string());
+                    self.output.push("    f32.eq".to_string());
+                } else {
+                    self.output.push("    i32.eqz".to_string());
+                }
+                self.output.push(format!("    br_if {}", label));
+            }
+        }
+    }
+
+    // Jumps to `label` when `cond` is truthy; falls through when it is falsy
+    fn gen_branch_if_true(&mut self, cond: &Expr, label: &str) {
+        match cond {
+            Expr::Logical(left, LogicalOp::Or, right) => {
+                self.gen_branch_if_true(left, label);
+                self.gen_branch_if_true(right, label);
+            }
+            Expr::Logical(left, LogicalOp::And, right) => {
+                let id = self.label_counter;
+                self.label_counter += 1;
+                let falsy = format!("$and_{}", id);
+
+                self.output.push(format!("    block {}", falsy));
+                self.gen_branch_if_false(left, &falsy);
+                self.gen_branch_if_true(right, label);
+                self.output.push("    end".to_string());
+            }
+            Expr::Unary(UnaryOp::Not, operand) => self.gen_branch_if_false(operand, label),
+            _ => {
+                self.gen_expr(cond);
+                if self.infer_expr_type_quick(cond) == Type::F32 {
+                    self.output.push("    f32.const 0.0".to_string());
+                    self.output.push("    f32.ne".to_string());
+                }
+                self.output.push(format!("    br_if {}", label));
+            }
+        }
+    }
+
+    fn is_host_call(&self, name: &str) -> bool {
+        !self.function_return_types.contains_key(name) && is_host_function(name)
+    }
+
+    // `return_call` needs the callee to produce exactly this function's results;
+    // otherwise `return f(x)` is a plain call and return
+    fn can_tail_call(&self, name: &str) -> bool {
+        !self.is_host_call(name) && self.function_return_types.get(name) == Some(&self.return_type)
+    }
+
+    fn is_void_call(&self, name: &str) -> bool {
+        self.is_host_call(name) || self.function_return_types.get(name) == Some(&None)
+    }
+
+    // Calls the import matching the argument's type; semantic analysis checked it exists
+    fn gen_host_call(&mut self, name: &str, args: &[Expr]) {
+        let arg_type = self.infer_expr_type_quick(&args[0]);
+        let Some(host) = host_function(name, arg_type) else {
+            return;
+        };
+        self.gen_expr(&args[0]);
+        if host.param == Type::Str {
+            self.output.push("    call $_print_str".to_string());
+        } else if self.wasi {
+            self.output.push("    call $_print_i32".to_string());
+        } else {
+            self.output.push(format!("    call ${}", host.import));
+        }
+        if !self.host_imports.contains(&host.import) {
+            self.host_imports.push(host.import);
+        }
+    }
+
+    fn gen_expr(&mut self, expr: &Expr) {
         match expr {
             Expr::Number(n) => {
                 self.output.push(format!("    i32.const {}", n));
@@ -461,22 +1520,50 @@ impl CodeGen {
             Expr::NumberF32(f) => {
                 self.output.push(format!("    f32.const {}", f));
             }
+            Expr::StringLit(s) => {
+                let addr = self.string_addr(s);
+                self.output.push(format!("    i32.const {}", addr));
+            }
+            Expr::Null => {
+                self.output.push(format!("    i32.const {}", NULL_SENTINEL));
+            }
+            Expr::ArrayLit(elements) => self.gen_array_lit(elements),
+            Expr::Index(array, index) => self.gen_index(array, index),
+            // `length`, the only member the analyzer accepts, is the word at the
+            // array's address
+            Expr::Member(object, _) => {
+                self.gen_expr(object);
+                self.output.push("    i32.load".to_string());
+            }
             Expr::Identifier(name) => {
-                self.output.push(format!("    local.get ${}", name));
+                self.output.push(format!("    local.get ${}", wat_id(name)));
             }
             Expr::Binary(left, op, right) => {
                 let left_type = self.infer_expr_type_quick(left);
                 let right_type = self.infer_expr_type_quick(right);
 
+                if left_type == Type::I32
+                    && right_type == Type::I32
+                    && self.arithmetic == ArithmeticMode::Checked
+                    && matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul)
+                {
+                    self.gen_checked_arith(left, op, right);
+                    return;
+                }
+                if *op == BinOp::Mod && (left_type == Type::F32 || right_type == Type::F32) {
+                    self.gen_f32_rem(left, right);
+                    return;
+                }
+
                 // Generate left operand
-                self.gen_expr(left, vars);
+                self.gen_expr(left);
                 // Convert if needed
                 if left_type == Type::I32 && right_type == Type::F32 {
                     self.output.push("    f32.convert_i32_s".to_string());
                 }
 
                 // Generate right operand
-                self.gen_expr(right, vars);
+                self.gen_expr(right);
                 // Convert if needed
                 if right_type == Type::I32 && left_type == Type::F32 {
                     self.output.push("    f32.convert_i32_s".to_string());
@@ -491,13 +1578,18 @@ impl CodeGen {
                         BinOp::Sub => "f32.sub",
                         BinOp::Mul => "f32.mul",
                         BinOp::Div => "f32.div",
-                        BinOp::Mod => "f32.rem",
+                        BinOp::Mod => unreachable!("f32 remainder is lowered by gen_f32_rem"),
                         BinOp::Eq => "f32.eq",
                         BinOp::Ne => "f32.ne",
                         BinOp::Lt => "f32.lt",
                         BinOp::Gt => "f32.gt",
                         BinOp::Le => "f32.le",
                         BinOp::Ge => "f32.ge",
+                        BinOp::And | BinOp::Or | BinOp::Xor | BinOp::Shl | BinOp::Shr => {
+                            unreachable!(
+                                "bitwise operators on f32 are rejected by semantic analysis"
+                            )
+                        }
                     }
                 } else {
                     match op {
@@ -512,6 +1604,11 @@ impl CodeGen {
                         BinOp::Gt => "i32.gt_s",
                         BinOp::Le => "i32.le_s",
                         BinOp::Ge => "i32.ge_s",
+                        BinOp::And => "i32.and",
+                        BinOp::Or => "i32.or",
+                        BinOp::Xor => "i32.xor",
+                        BinOp::Shl => "i32.shl",
+                        BinOp::Shr => "i32.shr_s",
                     }
                 };
                 self.output.push(format!("    {}", instr));
@@ -522,16 +1619,18 @@ impl CodeGen {
                 match op {
                     UnaryOp::Neg => {
                         if operand_type == Type::F32 {
-                            self.gen_expr(operand, vars);
+                            self.gen_expr(operand);
                             self.output.push("    f32.neg".to_string());
+                        } else if self.arithmetic == ArithmeticMode::Checked {
+                            self.gen_checked_arith(&Expr::Number(0), &BinOp::Sub, operand);
                         } else {
                             self.output.push("    i32.const 0".to_string());
-                            self.gen_expr(operand, vars);
+                            self.gen_expr(operand);
                             self.output.push("    i32.sub".to_string());
                         }
                     }
                     UnaryOp::Not => {
-                        self.gen_expr(operand, vars);
+                        self.gen_expr(operand);
                         if operand_type == Type::F32 {
                             // Convert f32 to boolean (0.0 = false, else = true)
                             self.output.push("    f32.const 0.0".to_string());
@@ -540,13 +1639,68 @@ impl CodeGen {
                             self.output.push("    i32.eqz".to_string());
                         }
                     }
+                    UnaryOp::BitNot => {
+                        // ~x is x ^ -1
+                        self.gen_expr(operand);
+                        self.output.push("    i32.const -1".to_string());
+                        self.output.push("    i32.xor".to_string());
+                    }
                 }
             }
             Expr::Call(name, args) => {
                 for arg in args {
-                    self.gen_expr(arg, vars);
+                    self.gen_expr(arg);
+                }
+                self.output.push(format!("    call ${}", wat_id(name)));
+            }
+            Expr::Ternary(cond, then_expr, else_expr) => {
+                let result_type = self.infer_expr_type_quick(expr);
+
+                // `if` takes any nonzero i32 as true; f32 is compared against 0.0
+                self.gen_expr(cond);
+                if self.infer_expr_type_quick(cond) == Type::F32 {
+                    self.output.push("    f32.const 0.0".to_string());
+                    self.output.push("    f32.ne".to_string());
+                }
+
+                self.output
+                    .push(format!("    if (result {})", type_to_wasm(result_type)));
+                self.gen_expr(then_expr);
+                if self.infer_expr_type_quick(then_expr) == Type::I32 && result_type == Type::F32 {
+                    self.output.push("    f32.convert_i32_s".to_string());
+                }
+                self.output.push("    else".to_string());
+                self.gen_expr(else_expr);
+                if self.infer_expr_type_quick(else_expr) == Type::I32 && result_type == Type::F32 {
+                    self.output.push("    f32.convert_i32_s".to_string());
+                }
+                self.output.push("    end".to_string());
+            }
+            // Stores the value and leaves it on the stack
+            Expr::Assign(name, value) => {
+                self.gen_expr(value);
+                self.output.push(format!("    local.tee ${}", wat_id(name)));
+            }
+            Expr::Update(op, name) => {
+                let one = if self.infer_expr_type_quick(expr) == Type::F32 {
+                    Expr::NumberF32(1.0)
+                } else {
+                    Expr::Number(1)
+                };
+                let step = Expr::Binary(
+                    Box::new(Expr::Identifier(name.clone())),
+                    op.bin_op(),
+                    Box::new(one),
+                );
+                if op.is_prefix() {
+                    self.gen_expr(&step);
+                    self.output.push(format!("    local.tee ${}", wat_id(name)));
+                } else {
+                    // The old value stays on the stack beneath the store
+                    self.output.push(format!("    local.get ${}", wat_id(name)));
+                    self.gen_expr(&step);
+                    self.output.push(format!("    local.set ${}", wat_id(name)));
                 }
-                self.output.push(format!("    call ${}", name));
             }
             Expr::Logical(left, op, right) => {
                 let left_type = self.infer_expr_type_quick(left);
@@ -559,7 +1713,7 @@ impl CodeGen {
 
                 match op {
                     LogicalOp::And => {
-                        self.gen_expr(left, vars);
+                        self.gen_expr(left);
 
                         // Convert left to result_type if needed
                         if left_type == Type::I32 && result_type == Type::F32 {
@@ -580,7 +1734,7 @@ impl CodeGen {
                             .push(format!("    if (result {})", type_to_wasm(result_type)));
                         self.output.push("    local.get $_result".to_string());
                         self.output.push("    else".to_string());
-                        self.gen_expr(right, vars);
+                        self.gen_expr(right);
 
                         // Convert right to result_type if needed
                         if right_type == Type::I32 && result_type == Type::F32 {
@@ -589,8 +1743,9 @@ impl CodeGen {
 
                         self.output.push("    end".to_string());
                     }
-                    LogicalOp::Or => {
-                        self.gen_expr(left, vars);
+                    // Without null, `a ?? b` keeps `a` unless it is zero, same as `||`
+                    LogicalOp::Or | LogicalOp::Nullish => {
+                        self.gen_expr(left);
 
                         // Convert left to result_type if needed
                         if left_type == Type::I32 && result_type == Type::F32 {
@@ -613,7 +1768,7 @@ impl CodeGen {
                             .push(format!("    if (result {})", type_to_wasm(result_type)));
                         self.output.push("    local.get $_result".to_string());
                         self.output.push("    else".to_string());
-                        self.gen_expr(right, vars);
+                        self.gen_expr(right);
 
                         // Convert right to result_type if needed
                         if right_type == Type::I32 && result_type == Type::F32 {
@@ -631,7 +1786,247 @@ impl CodeGen {
 // Helper function to convert Type to WASM type string
 fn type_to_wasm(t: Type) -> &'static str {
     match t {
-        Type::I32 => "i32",
+        Type::I32 | Type::Str | Type::Null | Type::Array => "i32",
+        Type::F32 => "f32",
+    }
+}
+
+// Simulates the operand stack through a function body, one instruction per line
+fn max_stack_depth(body: &[&str], signatures: &HashMap<String, (usize, usize)>) -> usize {
+    let mut depth: usize = 0;
+    let mut max = 0;
+    // Height at the start of each open block, and how many results it leaves
+    let mut frames: Vec<(usize, usize)> = Vec::new();
+    for line in body {
+        let mut words = line.split_whitespace();
+        let op = words.next().unwrap_or("");
+        let arg = words.next().unwrap_or("");
+        match op {
+            "block" | "loop" => frames.push((depth, 0)),
+            "if" => {
+                depth = depth.saturating_sub(1);
+                frames.push((depth, usize::from(arg == "(result")));
+            }
+            "else" => depth = frames.last().map_or(0, |&(base, _)| base),
+            "end" => {
+                if let Some((base, results)) = frames.pop() {
+                    depth = base + results;
+                }
+            }
+            // Nothing after an unconditional branch runs on the current stack
+            "br" | "return" | "return_call" | "unreachable" => {
+                depth = frames.last().map_or(0, |&(base, _)| base);
+            }
+            "call" => {
+                let (params, results) = signatures
+                    .get(arg.trim_start_matches('$'))
+                    .copied()
+                    .unwrap_or((0, 1));
+                depth = depth.saturating_sub(params) + results;
+            }
+            "local.get" | "global.get" | "memory.size" => depth += 1,
+            "i32.store" | "i32.store8" | "select" => depth = depth.saturating_sub(2),
+            op if op.ends_with(".const") => depth += 1,
+            "local.tee" | "i32.eqz" | "i32.load" | "i32.wrap_i64" | "i64.extend_i32_s"
+            | "f32.neg" | "f32.trunc" | "f32.convert_i32_s" | "memory.grow" => {}
+            // local.set, drop, br_if and the binary operators each pop one more than they push
+            _ => depth = depth.saturating_sub(1),
+        }
+        max = max.max(depth);
+    }
+    max
+}
+
+// Bytes as the body of a WAT string literal, escaping anything but printable ASCII
+fn wat_string(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for &b in bytes {
+        if (b == b' ' || b.is_ascii_graphic()) && b != b'"' && b != b'\\' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("\\{:02x}", b));
+        }
+    }
+    out
+}
+
+// WAT identifiers are non-empty runs of printable ASCII idchars
+fn is_valid_wat_id(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-./:<=>?@\\^_`|~".contains(c))
+}
+
+// Source identifiers may contain non-ASCII letters, which WAT ids can't. Each such
+// byte becomes `%XX`; `%` never appears in a source identifier, so names stay distinct.
+fn wat_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.chars() {
+        if c.is_ascii() {
+            id.push(c);
+        } else {
+            let mut bytes = [0; 4];
+            for b in c.encode_utf8(&mut bytes).bytes() {
+                id.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    id
+}
+
+// Component-level value type for a core type
+fn type_to_wit(t: Type) -> &'static str {
+    match t {
+        Type::I32 => "s32",
         Type::F32 => "f32",
+        Type::Str | Type::Null | Type::Array => {
+            unreachable!("string, null and array signatures are rejected by component_export_names")
+        }
+    }
+}
+
+// Component names must be kebab-case: `isPrime` and `is_prime` both become `is-prime`.
+// A segment can't start with a digit, so digits stay attached to the preceding word.
+fn kebab_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c == '_' {
+            if !out.is_empty() && !out.ends_with('-') {
+                out.push('-');
+            }
+            prev_lower = false;
+        } else if c.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('-');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_digit() && (out.is_empty() || out.ends_with('-')) {
+            out.pop();
+            out.push(c);
+            prev_lower = true;
+        } else {
+            out.push(c);
+            prev_lower = true;
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+// Escape names that collide with WIT keywords
+fn wit_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as",
+        "bool",
+        "borrow",
+        "char",
+        "constructor",
+        "enum",
+        "export",
+        "f32",
+        "f64",
+        "flags",
+        "from",
+        "func",
+        "import",
+        "include",
+        "interface",
+        "list",
+        "option",
+        "own",
+        "package",
+        "record",
+        "resource",
+        "result",
+        "s16",
+        "s32",
+        "s64",
+        "s8",
+        "static",
+        "string",
+        "tuple",
+        "type",
+        "u16",
+        "u32",
+        "u64",
+        "u8",
+        "use",
+        "variant",
+        "with",
+        "world",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("%{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// Every exported name with the function behind it, aliases right after their function
+fn exported_functions(program: &Program) -> Vec<(&str, &Function)> {
+    let mut exports = Vec::new();
+    for func in &program.functions {
+        exports.push((func.name.as_str(), func));
+        for alias in &func.aliases {
+            exports.push((alias.as_str(), func));
+        }
+    }
+    exports
+}
+
+// Kebab-case export names, rejecting functions that would collide after conversion
+fn component_export_names(program: &Program) -> Result<Vec<String>> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut names = Vec::new();
+    for (name, func) in exported_functions(program) {
+        // Component names are ASCII-only, unlike core export strings
+        let export_name = kebab_case(name);
+        if export_name.is_empty() || !export_name.is_ascii() {
+            return Err(CompilerError::codegen(
+                func.line,
+                format!("Function '{}' has no valid component export name", name),
+            )
+            .with_code(E_COMPONENT_EXPORT));
+        }
+        let unsupported = |t: &Type| matches!(t, Type::Str | Type::Null | Type::Array);
+        let uses_unsupported = func.return_type.as_ref().is_some_and(unsupported)
+            || func
+                .param_types
+                .as_ref()
+                .is_some_and(|types| types.iter().any(unsupported));
+        if uses_unsupported {
+            return Err(CompilerError::codegen(
+                func.line,
+                format!(
+                    "Function '{}' takes or returns a string, null or array, which components do not support yet",
+                    name
+                ),
+            )
+            .with_code(E_COMPONENT_EXPORT));
+        }
+        if let Some(param) = func.params.iter().find(|p| !p.is_ascii()) {
+            return Err(CompilerError::codegen(
+                func.line,
+                format!(
+                    "Parameter '{}' of function '{}' has no valid component name",
+                    param, name
+                ),
+            )
+            .with_code(E_COMPONENT_EXPORT));
+        }
+        if let Some(other) = seen.insert(export_name.clone(), name) {
+            return Err(CompilerError::codegen(
+                func.line,
+                format!(
+                    "Functions '{}' and '{}' both export as '{}' in a component",
+                    other, name, export_name
+                ),
+            )
+            .with_code(E_COMPONENT_EXPORT));
+        }
+        names.push(export_name);
     }
+    Ok(names)
 }
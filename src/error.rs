@@ -1,13 +1,20 @@
+use crate::ast::json_string;
 use std::fmt;
 
 #[derive(Debug, Clone)]
 pub struct CompilerError {
     pub line: usize,
+    /// 1-based column, or 0 when only the line is known
+    pub col: usize,
     pub message: String,
     pub error_type: ErrorType,
+    pub context: Vec<String>,
+    /// Stable code for the error class, e.g. `E0001`; see [`explain`].
+    pub code: &'static str,
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum ErrorType {
     LexerError,
     ParserError,
@@ -19,11 +26,30 @@ impl CompilerError {
     pub fn new(line: usize, message: String, error_type: ErrorType) -> Self {
         CompilerError {
             line,
+            col: 0,
             message,
             error_type,
+            context: Vec::new(),
+            code: "",
         }
     }
 
+    pub fn with_col(mut self, col: usize) -> Self {
+        self.col = col;
+        self
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Records an enclosing location (e.g. a call argument), innermost first.
+    pub fn with_context(mut self, frame: String) -> Self {
+        self.context.push(frame);
+        self
+    }
+
     pub fn lexer(line: usize, message: String) -> Self {
         Self::new(line, message, ErrorType::LexerError)
     }
@@ -41,6 +67,31 @@ impl CompilerError {
     }
 }
 
+impl CompilerError {
+    /// This error as a `--diagnostics json` entry. `line` and `column` are null when
+    /// unknown; only lexer and parser errors carry a column.
+    pub fn to_json(&self) -> String {
+        let error_type = match self.error_type {
+            ErrorType::LexerError => "lexer",
+            ErrorType::ParserError => "parser",
+            ErrorType::CodegenError => "codegen",
+            ErrorType::SemanticError => "semantic",
+        };
+        let mut message = self.message.clone();
+        if !self.context.is_empty() {
+            message = format!("{} ({})", message, self.context.join(", "));
+        }
+        diagnostic_json(
+            "error",
+            self.code,
+            self.line,
+            self.col,
+            &message,
+            Some(error_type),
+        )
+    }
+}
+
 impl fmt::Display for CompilerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let error_kind = match self.error_type {
@@ -49,10 +100,470 @@ impl fmt::Display for CompilerError {
             ErrorType::CodegenError => "Codegen Error",
             ErrorType::SemanticError => "Semantic Error",
         };
-        write!(f, "{} at line {}: {}", error_kind, self.line, self.message)
+        // Line 0 marks errors that are not tied to a source location
+        if self.line == 0 {
+            write!(f, "{}: {}", error_kind, self.message)?;
+        } else if self.col != 0 {
+            write!(
+                f,
+                "{} at line {}, col {}: {}",
+                error_kind, self.line, self.col, self.message
+            )?;
+        } else {
+            write!(f, "{} at line {}: {}", error_kind, self.line, self.message)?;
+        }
+        if !self.context.is_empty() {
+            write!(f, " ({})", self.context.join(", "))?;
+        }
+        if !self.code.is_empty() {
+            write!(f, " [{}]", self.code)?;
+        }
+        Ok(())
     }
 }
 
 impl std::error::Error for CompilerError {}
 
+pub const E_UNDEFINED_VARIABLE: &str = "E0001";
+pub const E_CONST_REASSIGN: &str = "E0002";
+pub const E_UNDEFINED_FUNCTION: &str = "E0003";
+pub const E_ARGUMENT_COUNT: &str = "E0004";
+pub const E_DUPLICATE_FUNCTION: &str = "E0005";
+pub const E_ASSIGN_TYPE: &str = "E0006";
+pub const E_PARAM_TYPE: &str = "E0007";
+pub const E_RETURN_TYPE: &str = "E0008";
+pub const E_F32_MODULO: &str = "E0009";
+pub const E_OUTSIDE_LOOP: &str = "E0010";
+pub const E_FUNCTION_AS_VALUE: &str = "E0011";
+pub const E_CONST_OVERFLOW: &str = "E0012";
+pub const E_UNEXPECTED_CHAR: &str = "E0013";
+pub const E_UNTERMINATED_COMMENT: &str = "E0014";
+pub const E_INVALID_NUMBER: &str = "E0015";
+pub const E_TAB: &str = "E0016";
+pub const E_UNEXPECTED_TOKEN: &str = "E0017";
+pub const E_UNSUPPORTED: &str = "E0018";
+pub const E_MODULE_NAME: &str = "E0019";
+pub const E_COMPONENT_EXPORT: &str = "E0020";
+pub const E_ASSEMBLE: &str = "E0021";
+pub const E_F32_BITWISE: &str = "E0022";
+pub const E_UNTERMINATED_STRING: &str = "E0023";
+pub const E_INVALID_ESCAPE: &str = "E0024";
+pub const E_STRING_TYPE: &str = "E0025";
+pub const E_VOID_VALUE: &str = "E0026";
+pub const E_MISSING_RETURN: &str = "E0027";
+pub const E_CONST_INIT: &str = "E0028";
+pub const E_NULL_TYPE: &str = "E0029";
+pub const E_ARRAY_TYPE: &str = "E0030";
+pub const E_WASI_PRINT: &str = "E0031";
+
+const EXPLANATIONS: &[(&str, &str)] = &[
+    (
+        E_UNDEFINED_VARIABLE,
+        "A variable was used before it was declared, or outside the block that declares it.
+
+    let total = count + 1;   // 'count' was never declared
+
+Declare it first with `let` or `const`. Variables declared inside a block,
+including a `for` header, are not visible after the block ends.",
+    ),
+    (
+        E_CONST_REASSIGN,
+        "A variable declared with `const` was assigned a new value.
+
+    const limit = 10;
+    limit = 20;              // not allowed
+
+Declare it with `let` if it needs to change.",
+    ),
+    (
+        E_UNDEFINED_FUNCTION,
+        "A call names a function that is not defined anywhere in the program.
+
+    let x = sqaure(4);       // typo for 'square'
+
+Functions may be defined before or after the call, but must exist.",
+    ),
+    (
+        E_ARGUMENT_COUNT,
+        "A function was called with a different number of arguments than it declares.
+
+    function add(a, b) { return a + b; }
+    add(1);                  // expects 2 arguments
+
+Unlike JavaScript, missing arguments are not filled with `undefined`.",
+    ),
+    (
+        E_DUPLICATE_FUNCTION,
+        "Two functions share the same name. Each function becomes a WebAssembly
+export, so names must be unique. Rename or remove one of them.
+
+Under --entry=export the top-level code is exported as `main`, so no function
+may be named `main` either.",
+    ),
+    (
+        E_ASSIGN_TYPE,
+        "A value of one type was assigned to a variable of the other type.
+
+    let n = 1;
+    n = 2.5;                 // n is i32, 2.5 is f32
+
+A variable's type is fixed by its initializer. Initialize it with a float
+(`let n = 1.0;`) if it needs to hold f32 values.",
+    ),
+    (
+        E_PARAM_TYPE,
+        "A call passes a different type than an earlier call fixed for a parameter.
+
+    mul(2, 3);               // first call: a and b are i32
+    mul(2.5, 3);             // a is already i32
+
+Parameter types come from the first call (\"first-call wins\"). Make the
+first call use floats if the function should take f32.",
+    ),
+    (
+        E_RETURN_TYPE,
+        "A function returns i32 on one path and f32 on another. A WebAssembly
+function has a single result type, so every `return` must agree.",
+    ),
+    (
+        E_F32_MODULO,
+        "No longer reported. `%` on f32 values used to be rejected; it is now
+computed as `a - b * trunc(a / b)`, which like JS keeps the sign of `a`.",
+    ),
+    (
+        E_OUTSIDE_LOOP,
+        "`break` appears outside any loop or `switch`, or `continue` outside any
+loop, so there is nothing for it to leave or restart.",
+    ),
+    (
+        E_FUNCTION_AS_VALUE,
+        "A function name was used as a value instead of being called.
+
+    let f = square;          // functions are not values
+
+Call it instead: `square(4)`.",
+    ),
+    (
+        E_CONST_OVERFLOW,
+        "Under --checked-arithmetic, a constant expression overflows i32.
+
+    let x = 2147483647 + 1;
+
+The same expression would trap at runtime, so it is rejected at compile time.",
+    ),
+    (
+        E_UNEXPECTED_CHAR,
+        "The source contains a character the language does not use, such as `@` or `#`.",
+    ),
+    (
+        E_UNTERMINATED_COMMENT,
+        "A `/*` block comment is never closed with `*/` before the end of the file.",
+    ),
+    (
+        E_INVALID_NUMBER,
+        "A numeric literal is malformed or out of range: an exponent without
+digits (`1e`), a `0x`, `0b` or `0o` prefix without digits or with a digit
+outside its base (`0xZZ`), or an integer above 2147483647, the largest i32.
+2147483648 is accepted only when negated, as `-2147483648`.",
+    ),
+    (
+        E_TAB,
+        "A tab character was found while --no-tabs is set. Indent with spaces.",
+    ),
+    (
+        E_UNEXPECTED_TOKEN,
+        "The parser found a token where the grammar expects something else,
+such as a missing `;`, `)` or `}`, or a keyword used as a name.
+
+    let x = 5                // missing ';'",
+    ),
+    (
+        E_UNSUPPORTED,
+        "The source uses a JavaScript keyword that this compiler does not
+support, such as `new`, `typeof` or `in`.",
+    ),
+    (
+        E_MODULE_NAME,
+        "The --module-name value is not a valid WAT identifier. Use letters,
+digits and ASCII symbols such as `_`, `.` and `-`, without spaces.",
+    ),
+    (
+        E_COMPONENT_EXPORT,
+        "Under --component, each function is exported under its kebab-case name.
+The name converted to nothing, contains non-ASCII letters (component names
+and their parameter names are ASCII-only), or two functions convert to the
+same name (`getValue` and `get_value` both become `get-value`).
+
+Strings are not supported under --component yet: an exported function may
+not take or return one, and `print` is unavailable.",
+    ),
+    (
+        E_ASSEMBLE,
+        "The generated WAT could not be assembled into a binary module. This is
+a compiler bug; please report it with the input program.",
+    ),
+    (
+        E_F32_BITWISE,
+        "A bitwise operator (`&`, `|`, `^`, `~`, `<<`, `>>`) was used on an f32
+value. They only apply to i32; JavaScript would truncate the float first,
+which this compiler does not do implicitly.",
+    ),
+    (
+        E_UNTERMINATED_STRING,
+        "A string literal is not closed with its matching quote before the end
+of the line. Strings cannot span lines; write `\\n` for a line break.",
+    ),
+    (
+        E_INVALID_ESCAPE,
+        "A string literal contains a backslash escape this compiler does not
+recognize. The supported escapes are `\\n`, `\\t`, `\\r`, `\\\\`, `\\\"` and `\\'`.",
+    ),
+    (
+        E_STRING_TYPE,
+        "A string was used where a number is expected, or a number where a string
+is expected.
+
+    let s = \"hi\";
+    let n = s + 1;           // strings have no operators
+
+Strings can be stored in variables, passed to and returned from functions,
+chosen with `?:`, and printed with `print`.",
+    ),
+    (
+        E_VOID_VALUE,
+        "A function that returns nothing was used as a value. Host functions such
+as `print`, and functions without a `return`, can only be called as statements.
+
+    let x = print(42);       // print has no result
+
+Call it on its own line instead: `print(42);`.",
+    ),
+    (
+        E_MISSING_RETURN,
+        "A function returns a value on some paths but can reach the end of its
+body on others, where it would have no value to return.
+
+    function sign(x) {
+        if (x > 0) { return 1; }
+        if (x < 0) { return -1; }
+    }                        // x == 0 falls through
+
+An `if` returns on every path only if both of its branches do, and a loop
+never counts, since its body may not run. Add a final `return`, or an
+`else` so that every branch returns.",
+    ),
+    (
+        E_CONST_INIT,
+        "A `const` was declared without a value. A `const` can never be assigned
+later, so it must be given its value where it is declared.
+
+    const limit;             // missing `= value`
+
+A `let` may leave out its value; it then starts as 0.",
+    ),
+    (
+        E_NULL_TYPE,
+        "`null` was used as an operand or a condition.
+
+    let x = null;
+    let y = x + 1;           // null has no operators
+
+A null can be stored in a variable, passed to and returned from functions,
+and chosen with `?:` when both branches are null.",
+    ),
+    (
+        E_ARRAY_TYPE,
+        "An array was used as a number, or something else was used as an array.
+
+    let a = [1, 2, 3];
+    let b = a + 1;           // arrays have no operators
+    let c = [1.5];           // elements must be i32
+    let d = b[0];            // only arrays can be indexed
+    let e = a[0.5];          // indexes must be i32
+    let f = a.size;          // arrays only have `length`
+
+An array can be stored in a variable, passed to and returned from functions,
+indexed with `a[i]`, measured with `a.length`, and chosen with `?:` when both
+branches are arrays.",
+    ),
+    (
+        E_WASI_PRINT,
+        "Under --wasi, `print` writes to stdout through WASI `fd_write`, which
+can only print i32 values and strings so far.
+
+    print(1.5);              // no f32 formatting under --wasi
+
+Compile without --wasi to call the `env` imports instead.",
+    ),
+];
+
+/// The long description of an error code, for `--explain`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, text)| *text)
+}
+
+// Source lines longer than this are cut down to a window around the column
+const SNIPPET_WIDTH: usize = 80;
+
+/// The error followed, when it has a column, by its source line and a `^` under
+/// the offending character, rustc-style. Errors without a column render as their
+/// `Display` text alone.
+///
+/// ```
+/// use js_to_wasm_compiler::error::{render, CompilerError};
+///
+/// let error = CompilerError::parser(2, "Expected identifier, got '='".to_string()).with_col(5);
+/// let rendered = render(&error, "let a = 1;\nlet = 5;\n");
+/// assert_eq!(
+///     rendered,
+///     "Parser Error at line 2, col 5: Expected identifier, got '='\n  |\n2 | let = 5;\n  |     ^"
+/// );
+/// ```
+pub fn render(error: &CompilerError, source: &str) -> String {
+    if error.line == 0 || error.col == 0 {
+        return error.to_string();
+    }
+    // An error at end of input can sit on the empty line after a final newline
+    let line: Vec<char> = source
+        .lines()
+        .nth(error.line - 1)
+        .unwrap_or("")
+        .chars()
+        .collect();
+    let caret = error.col - 1;
+
+    let (mut start, mut end) = (0, line.len());
+    if line.len() > SNIPPET_WIDTH {
+        start = caret.saturating_sub(SNIPPET_WIDTH / 2);
+        end = (start + SNIPPET_WIDTH).min(line.len());
+        start = end.saturating_sub(SNIPPET_WIDTH);
+    }
+    let mut text: String = line[start..end].iter().collect();
+    // Tabs in front of the column are repeated so the caret lines up with them
+    let mut padding: String = line[start..caret.min(end)]
+        .iter()
+        .map(|&c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    padding.extend(std::iter::repeat_n(' ', caret.saturating_sub(end)));
+    if start > 0 {
+        text.insert_str(0, "...");
+        padding.insert_str(0, "   ");
+    }
+    if end < line.len() {
+        text.push_str("...");
+    }
+
+    let number = error.line.to_string();
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{}\n{} |\n{} | {}\n{} | {}^",
+        error, gutter, number, text, gutter, padding
+    )
+}
+
+/// Lints that warn without stopping compilation; each can be silenced with `--allow <name>`,
+/// and those off by default turned on with `--warn <name>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarningKind {
+    TooManyParams,
+    UnusedResult,
+    ShiftAmount,
+    IntDivision,
+    UnusedVariable,
+}
+
+impl WarningKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningKind::TooManyParams => "too-many-params",
+            WarningKind::UnusedResult => "unused-result",
+            WarningKind::ShiftAmount => "shift-amount",
+            WarningKind::IntDivision => "int-division",
+            WarningKind::UnusedVariable => "unused-variable",
+        }
+    }
+
+    /// Teaching aids such as `int-division` are opt-in.
+    pub fn enabled_by_default(self) -> bool {
+        !matches!(self, WarningKind::IntDivision | WarningKind::UnusedVariable)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "too-many-params" => Some(WarningKind::TooManyParams),
+            "unused-result" => Some(WarningKind::UnusedResult),
+            "shift-amount" => Some(WarningKind::ShiftAmount),
+            "int-division" => Some(WarningKind::IntDivision),
+            "unused-variable" => Some(WarningKind::UnusedVariable),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompilerWarning {
+    pub line: usize,
+    pub message: String,
+    pub kind: WarningKind,
+}
+
+impl CompilerWarning {
+    pub fn new(line: usize, message: String, kind: WarningKind) -> Self {
+        CompilerWarning {
+            line,
+            message,
+            kind,
+        }
+    }
+}
+
+impl CompilerWarning {
+    /// This warning as a `--diagnostics json` entry; its code is the lint name.
+    pub fn to_json(&self) -> String {
+        diagnostic_json(
+            "warning",
+            self.kind.name(),
+            self.line,
+            0,
+            &self.message,
+            None,
+        )
+    }
+}
+
+fn diagnostic_json(
+    severity: &str,
+    code: &str,
+    line: usize,
+    col: usize,
+    message: &str,
+    error_type: Option<&str>,
+) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+    format!(
+        "{{\"severity\":\"{}\",\"code\":{},\"line\":{},\"column\":{},\"message\":{},\"error_type\":{}}}",
+        severity,
+        optional(Some(code).filter(|c| !c.is_empty()).map(json_string)),
+        optional(Some(line).filter(|&l| l != 0).map(|l| l.to_string())),
+        optional(Some(col).filter(|&c| c != 0).map(|c| c.to_string())),
+        json_string(message),
+        optional(error_type.map(json_string))
+    )
+}
+
+impl fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Warning at line {}: {} [{}]",
+            self.line,
+            self.message,
+            self.kind.name()
+        )
+    }
+}
+
 pub type Result<T> = std::result::Result<T, CompilerError>;
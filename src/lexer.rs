@@ -1,9 +1,14 @@
-use crate::error::{CompilerError, Result};
+use crate::error::{
+    CompilerError, Result, E_INVALID_ESCAPE, E_INVALID_NUMBER, E_TAB, E_UNEXPECTED_CHAR,
+    E_UNTERMINATED_COMMENT, E_UNTERMINATED_STRING,
+};
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Number(i32),
     NumberF32(f32),
+    String(String),
     Identifier(String),
 
     // Keywords
@@ -13,14 +18,25 @@ pub enum Token {
     If,
     Else,
     While,
+    Do,
     For,
     Return,
     Break,
     Continue,
+    Switch,
+    Case,
+    Default,
+    True,
+    False,
+    Null,
+    // JS keywords this compiler recognizes but does not support
+    Reserved(String),
 
     // Operators
     Plus,
     Minus,
+    PlusPlus,
+    MinusMinus,
     Star,
     Slash,
     Percent,
@@ -33,25 +49,113 @@ pub enum Token {
     GtEq,
     Eq,
 
+    // Bitwise
+    Amp,
+    Pipe,
+    Caret,
+    Tilde,
+    Shl,
+    Shr,
+
     // Logical
     AndAnd,
     OrOr,
+    QuestionQuestion,
+
+    // Conditional
+    Question,
+    Colon,
 
     // Delimiters
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
+    Dot,
     Comma,
     Semicolon,
 
     Eof,
 }
 
+/// How a token reads in an error message: keywords and symbols as written.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Token::Number(n) => return write!(f, "number {}", n),
+            Token::NumberF32(n) => return write!(f, "number {}", n),
+            Token::String(s) => return write!(f, "string {:?}", s),
+            Token::Identifier(name) => return write!(f, "identifier '{}'", name),
+            Token::Reserved(word) => return write!(f, "'{}'", word),
+            Token::Eof => return write!(f, "end of input"),
+            Token::Let => "let",
+            Token::Const => "const",
+            Token::Function => "function",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::While => "while",
+            Token::Do => "do",
+            Token::For => "for",
+            Token::Return => "return",
+            Token::Break => "break",
+            Token::Continue => "continue",
+            Token::Switch => "switch",
+            Token::Case => "case",
+            Token::Default => "default",
+            Token::True => "true",
+            Token::False => "false",
+            Token::Null => "null",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::PlusPlus => "++",
+            Token::MinusMinus => "--",
+            Token::Star => "*",
+            Token::Slash => "/",
+            Token::Percent => "%",
+            Token::Bang => "!",
+            Token::EqEq => "==",
+            Token::BangEq => "!=",
+            Token::Lt => "<",
+            Token::Gt => ">",
+            Token::LtEq => "<=",
+            Token::GtEq => ">=",
+            Token::Eq => "=",
+            Token::Amp => "&",
+            Token::Pipe => "|",
+            Token::Caret => "^",
+            Token::Tilde => "~",
+            Token::Shl => "<<",
+            Token::Shr => ">>",
+            Token::AndAnd => "&&",
+            Token::OrOr => "||",
+            Token::QuestionQuestion => "??",
+            Token::Question => "?",
+            Token::Colon => ":",
+            Token::LParen => "(",
+            Token::RParen => ")",
+            Token::LBrace => "{",
+            Token::RBrace => "}",
+            Token::LBracket => "[",
+            Token::RBracket => "]",
+            Token::Dot => ".",
+            Token::Comma => ",",
+            Token::Semicolon => ";",
+        };
+        write!(f, "'{}'", symbol)
+    }
+}
+
 pub struct Lexer {
     input: Vec<char>,
     pos: usize,
     line: usize,
+    /// 1-based column of `pos` within its line, counted in characters
+    col: usize,
+    reject_tabs: bool,
+    /// Whether the last token was `-`, so a literal of 2^31 can become i32::MIN
+    after_minus: bool,
 }
 impl Lexer {
     pub fn new(input: &str) -> Self {
@@ -59,9 +163,18 @@ impl Lexer {
             input: input.chars().collect(),
             pos: 0,
             line: 1,
+            col: 1,
+            reject_tabs: false,
+            after_minus: false,
         }
     }
 
+    /// Style enforcement: report tab characters in whitespace as errors.
+    pub fn reject_tabs(mut self, reject: bool) -> Self {
+        self.reject_tabs = reject;
+        self
+    }
+
     fn peek(&self) -> char {
         self.input.get(self.pos).copied().unwrap_or('\0')
     }
@@ -71,14 +184,26 @@ impl Lexer {
         self.pos += 1;
         if c == '\n' {
             self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
         }
         c
     }
 
-    fn skip_whitespace(&mut self) {
+    fn skip_whitespace(&mut self) -> Result<()> {
         while self.peek().is_whitespace() {
+            if self.reject_tabs && self.peek() == '\t' {
+                return Err(CompilerError::lexer(
+                    self.line,
+                    "Tabs are not allowed; use spaces".to_string(),
+                )
+                .with_col(self.col)
+                .with_code(E_TAB));
+            }
             self.advance();
         }
+        Ok(())
     }
 
     fn skip_line_comment(&mut self) {
@@ -88,14 +213,18 @@ impl Lexer {
     }
 
     fn skip_block_comment(&mut self) -> Result<()> {
+        // Reported at the `/*`, since the end of the file says nothing about
+        // which comment was left open
+        let (line, col) = (self.line, self.col);
         self.advance(); // consume '/'
         self.advance(); // consume '*'
         loop {
             if self.peek() == '\0' {
-                return Err(CompilerError::lexer(
-                    self.line,
-                    "Unterminated block comment".to_string(),
-                ));
+                return Err(
+                    CompilerError::lexer(line, "Unterminated block comment".to_string())
+                        .with_col(col)
+                        .with_code(E_UNTERMINATED_COMMENT),
+                );
             }
             if self.peek() == '*' && self.input.get(self.pos + 1) == Some(&'/') {
                 self.advance(); // consume '*'
@@ -107,11 +236,24 @@ impl Lexer {
         Ok(())
     }
 
-    fn read_number(&mut self) -> Result<(Token, usize)> {
+    fn read_number(&mut self) -> Result<(Token, usize, usize)> {
         let start_line = self.line;
+        let start_col = self.col;
         let mut num_str = String::new();
         let mut is_float = false;
 
+        if self.peek() == '0' {
+            let radix = match self.input.get(self.pos + 1) {
+                Some('x') | Some('X') => Some(16),
+                Some('b') | Some('B') => Some(2),
+                Some('o') | Some('O') => Some(8),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                return self.read_radix_number(radix, start_line, start_col);
+            }
+        }
+
         // Read integer part
         while self.peek().is_ascii_digit() {
             num_str.push(self.advance());
@@ -160,7 +302,9 @@ impl Lexer {
                 return Err(CompilerError::lexer(
                     start_line,
                     "Invalid number: expected digit after exponent".to_string(),
-                ));
+                )
+                .with_col(start_col)
+                .with_code(E_INVALID_NUMBER));
             }
             while self.peek().is_ascii_digit() {
                 num_str.push(self.advance());
@@ -170,21 +314,125 @@ impl Lexer {
         // Parse the string
         if is_float {
             match num_str.parse::<f32>() {
-                Ok(f) => Ok((Token::NumberF32(f), start_line)),
+                Ok(f) => Ok((Token::NumberF32(f), start_line, start_col)),
                 Err(_) => Err(CompilerError::lexer(
                     start_line,
                     format!("Invalid float literal: {}", num_str),
-                )),
+                )
+                .with_col(start_col)
+                .with_code(E_INVALID_NUMBER)),
             }
         } else {
+            // The digit run is always well-formed, so parsing can only fail on range
             match num_str.parse::<i32>() {
-                Ok(n) => Ok((Token::Number(n), start_line)),
+                Ok(n) => Ok((Token::Number(n), start_line, start_col)),
+                Err(_) if self.negates_to_min(num_str.parse().ok()) => {
+                    Ok((Token::Number(i32::MIN), start_line, start_col))
+                }
                 Err(_) => Err(CompilerError::lexer(
                     start_line,
-                    format!("Invalid integer literal: {}", num_str),
-                )),
+                    format!("Integer literal too large: {} (max {})", num_str, i32::MAX),
+                )
+                .with_col(start_col)
+                .with_code(E_INVALID_NUMBER)),
+            }
+        }
+    }
+
+    // Reads `0x`, `0b` or `0o` and the digits after it. Letters and digits run
+    // together, so `0xZZ` is one invalid literal rather than `0x` then `ZZ`.
+    fn read_radix_number(
+        &mut self,
+        radix: u32,
+        start_line: usize,
+        start_col: usize,
+    ) -> Result<(Token, usize, usize)> {
+        let mut prefix = String::new();
+        prefix.push(self.advance()); // consume '0'
+        prefix.push(self.advance()); // consume 'x', 'b' or 'o'
+        let mut digits = String::new();
+        while self.peek().is_ascii_alphanumeric() {
+            digits.push(self.advance());
+        }
+        let literal = format!("{}{}", prefix, digits);
+        let error = |message: String| {
+            Err(CompilerError::lexer(start_line, message)
+                .with_col(start_col)
+                .with_code(E_INVALID_NUMBER))
+        };
+
+        if digits.is_empty() {
+            return error(format!("Invalid number: expected digits after {}", prefix));
+        }
+        if let Some(bad) = digits.chars().find(|c| !c.is_digit(radix)) {
+            return error(format!("Invalid digit '{}' in {}", bad, literal));
+        }
+        // Every digit is valid, so parsing can only fail on range
+        match i32::from_str_radix(&digits, radix) {
+            Ok(n) => Ok((Token::Number(n), start_line, start_col)),
+            Err(_) if self.negates_to_min(u32::from_str_radix(&digits, radix).ok()) => {
+                Ok((Token::Number(i32::MIN), start_line, start_col))
+            }
+            Err(_) => error(format!(
+                "Integer literal too large: {} (max {})",
+                literal,
+                i32::MAX
+            )),
+        }
+    }
+
+    // 2^31 is one past i32::MAX, but right after `-` it is the magnitude of
+    // i32::MIN. It lexes as i32::MIN and the parser takes it only as `-`'s operand.
+    fn negates_to_min(&self, magnitude: Option<u32>) -> bool {
+        self.after_minus && magnitude == Some(1 << 31)
+    }
+
+    // Reads a '...' or "..." literal, resolving escapes
+    fn read_string(&mut self) -> Result<(Token, usize, usize)> {
+        let start_line = self.line;
+        let start_col = self.col;
+        let quote = self.advance();
+        let mut s = String::new();
+        loop {
+            if self.pos >= self.input.len() || self.peek() == '\n' {
+                return Err(CompilerError::lexer(
+                    start_line,
+                    "Unterminated string literal".to_string(),
+                )
+                .with_col(start_col)
+                .with_code(E_UNTERMINATED_STRING));
+            }
+            let c = self.advance();
+            if c == quote {
+                break;
+            }
+            if c != '\\' {
+                s.push(c);
+                continue;
             }
+            if self.pos >= self.input.len() || self.peek() == '\n' {
+                continue; // Reported as unterminated above
+            }
+            let escaped = match self.peek() {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '\\' => '\\',
+                '"' => '"',
+                '\'' => '\'',
+                other => {
+                    return Err(CompilerError::lexer(
+                        self.line,
+                        format!("Invalid escape sequence: \\{}", other),
+                    )
+                    .with_col(self.col - 1)
+                    .with_code(E_INVALID_ESCAPE))
+                }
+            };
+            self.advance();
+            s.push(escaped);
         }
+        Ok((Token::String(s), start_line, start_col))
     }
 
     fn read_identifier(&mut self) -> String {
@@ -195,8 +443,9 @@ impl Lexer {
         s
     }
 
-    pub fn next_token(&mut self) -> Result<(Token, usize)> {
-        self.skip_whitespace();
+    /// The next token with the line and column of its first character.
+    pub fn next_token(&mut self) -> Result<(Token, usize, usize)> {
+        self.skip_whitespace()?;
 
         // Single-line comment
         if self.peek() == '/' && self.input.get(self.pos + 1) == Some(&'/') {
@@ -211,10 +460,11 @@ impl Lexer {
         }
 
         let line = self.line;
+        let col = self.col;
         let c = self.peek();
 
         if c == '\0' {
-            return Ok((Token::Eof, line));
+            return Ok((Token::Eof, line, col));
         }
 
         // Handle .5 style floats
@@ -222,7 +472,7 @@ impl Lexer {
             && self
                 .input
                 .get(self.pos + 1)
-                .map_or(false, |ch| ch.is_ascii_digit())
+                .is_some_and(|ch| ch.is_ascii_digit())
         {
             return self.read_number();
         }
@@ -231,6 +481,10 @@ impl Lexer {
             return self.read_number();
         }
 
+        if c == '"' || c == '\'' {
+            return self.read_string();
+        }
+
         if c.is_alphabetic() || c == '_' {
             let ident = self.read_identifier();
             let tok = match ident.as_str() {
@@ -240,19 +494,43 @@ impl Lexer {
                 "if" => Token::If,
                 "else" => Token::Else,
                 "while" => Token::While,
+                "do" => Token::Do,
                 "for" => Token::For,
                 "return" => Token::Return,
                 "break" => Token::Break,
                 "continue" => Token::Continue,
+                "switch" => Token::Switch,
+                "case" => Token::Case,
+                "default" => Token::Default,
+                "true" => Token::True,
+                "false" => Token::False,
+                "null" => Token::Null,
+                "new" | "delete" | "in" | "typeof" | "instanceof" | "class" => {
+                    Token::Reserved(ident)
+                }
                 _ => Token::Identifier(ident),
             };
-            return Ok((tok, line));
+            return Ok((tok, line, col));
         }
 
         self.advance();
         let tok = match c {
-            '+' => Token::Plus,
-            '-' => Token::Minus,
+            '+' => {
+                if self.peek() == '+' {
+                    self.advance();
+                    Token::PlusPlus
+                } else {
+                    Token::Plus
+                }
+            }
+            '-' => {
+                if self.peek() == '-' {
+                    self.advance();
+                    Token::MinusMinus
+                } else {
+                    Token::Minus
+                }
+            }
             '*' => Token::Star,
             '/' => Token::Slash,
             '%' => Token::Percent,
@@ -260,8 +538,13 @@ impl Lexer {
             ')' => Token::RParen,
             '{' => Token::LBrace,
             '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '.' => Token::Dot,
             ',' => Token::Comma,
             ';' => Token::Semicolon,
+            '^' => Token::Caret,
+            '~' => Token::Tilde,
             '!' => {
                 if self.peek() == '=' {
                     self.advance();
@@ -282,6 +565,9 @@ impl Lexer {
                 if self.peek() == '=' {
                     self.advance();
                     Token::LtEq
+                } else if self.peek() == '<' {
+                    self.advance();
+                    Token::Shl
                 } else {
                     Token::Lt
                 }
@@ -290,47 +576,49 @@ impl Lexer {
                 if self.peek() == '=' {
                     self.advance();
                     Token::GtEq
+                } else if self.peek() == '>' {
+                    self.advance();
+                    Token::Shr
                 } else {
                     Token::Gt
                 }
             }
-            '&' => {
-                if self.peek() == '&' {
-                    self.advance();
-                    Token::AndAnd
-                } else {
-                    return Err(CompilerError::lexer(
-                        line,
-                        format!("Unexpected character: {}", c),
-                    ));
-                }
+            '&' if self.peek() == '&' => {
+                self.advance();
+                Token::AndAnd
+            }
+            '|' if self.peek() == '|' => {
+                self.advance();
+                Token::OrOr
             }
-            '|' => {
-                if self.peek() == '|' {
+            '?' => {
+                if self.peek() == '?' {
                     self.advance();
-                    Token::OrOr
+                    Token::QuestionQuestion
                 } else {
-                    return Err(CompilerError::lexer(
-                        line,
-                        format!("Unexpected character: {}", c),
-                    ));
+                    Token::Question
                 }
             }
+            ':' => Token::Colon,
+            '&' => Token::Amp,
+            '|' => Token::Pipe,
             _ => {
-                return Err(CompilerError::lexer(
-                    line,
-                    format!("Unexpected character: {}", c),
-                ))
+                return Err(
+                    CompilerError::lexer(line, format!("Unexpected character: {}", c))
+                        .with_col(col)
+                        .with_code(E_UNEXPECTED_CHAR),
+                )
             }
         };
-        Ok((tok, line))
+        Ok((tok, line, col))
     }
-    pub fn tokenize(&mut self) -> Result<Vec<(Token, usize)>> {
+    pub fn tokenize(&mut self) -> Result<Vec<(Token, usize, usize)>> {
         let mut tokens = Vec::new();
         loop {
-            let (tok, line) = self.next_token()?;
+            let (tok, line, col) = self.next_token()?;
             let is_eof = tok == Token::Eof;
-            tokens.push((tok, line));
+            self.after_minus = tok == Token::Minus;
+            tokens.push((tok, line, col));
             if is_eof {
                 break;
             }
@@ -0,0 +1,231 @@
+//! JavaScript-subset to WebAssembly (WAT) compiler.
+//!
+//! [`compile`] runs the whole pipeline with default settings:
+//! [`Lexer`] → [`Parser`] → [`SemanticAnalyzer`] → [`optimize_program`] → [`CodeGen`].
+//! [`compile_with_options`] takes the CLI's settings (component output, checked
+//! arithmetic, warnings) as [`CompileOptions`], and [`lex`] and [`build_program`]
+//! stop partway for callers that want the tokens or the checked AST.
+
+pub mod ast;
+pub mod codegen;
+pub mod error;
+pub mod lexer;
+pub mod optimizer;
+pub mod parser;
+pub mod semantic;
+
+pub use codegen::{CodeGen, Entry};
+pub use error::{CompilerError, CompilerWarning, Result, WarningKind};
+pub use lexer::Lexer;
+pub use optimizer::{optimize_program, OptLevel, Pass};
+pub use parser::Parser;
+pub use semantic::SemanticAnalyzer;
+
+use ast::{ArithmeticMode, Program};
+use error::E_ASSEMBLE;
+use lexer::Token;
+use optimizer::run_passes;
+
+/// Settings for each stage, as the CLI's flags set them. The defaults are what
+/// [`compile`] uses.
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// Reject tab characters (`--no-tabs`)
+    pub reject_tabs: bool,
+    /// Warning kinds to silence (`--allow`)
+    pub allowed: Vec<WarningKind>,
+    /// Warning kinds that are off by default to turn on (`--warn`)
+    pub enabled: Vec<WarningKind>,
+    /// Parameter count above which a function gets a warning (`--max-params`)
+    pub max_params: usize,
+    pub arithmetic: ArithmeticMode,
+    pub opt_level: OptLevel,
+    pub module_name: Option<String>,
+    pub component: bool,
+    pub tail_calls: bool,
+    pub wasi: bool,
+    pub entry: Entry,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            reject_tabs: false,
+            allowed: Vec::new(),
+            enabled: Vec::new(),
+            max_params: 8,
+            arithmetic: ArithmeticMode::Wrapping,
+            opt_level: OptLevel::O2,
+            module_name: None,
+            component: false,
+            tail_calls: true,
+            wasi: false,
+            entry: Entry::Start,
+        }
+    }
+}
+
+impl CompileOptions {
+    /// A code generator with these settings, for rendering a program from
+    /// [`build_program`] as WAT, WIT or a report.
+    pub fn codegen(&self) -> CodeGen {
+        CodeGen::new()
+            .module_name(self.module_name.clone())
+            .component(self.component)
+            .arithmetic(self.arithmetic)
+            .tail_calls(self.tail_calls)
+            .wasi(self.wasi)
+            .entry(self.entry)
+    }
+}
+
+/// Compiles JavaScript source to a WAT module. Warnings are discarded.
+///
+/// ```
+/// let wat = js_to_wasm_compiler::compile("function f() { return 1 + 2; }").unwrap();
+/// assert!(wat.contains("i32.const 3"));
+/// assert!(!wat.contains("i32.add"));
+/// ```
+pub fn compile(source: &str) -> Result<String> {
+    compile_with_passes(source, Vec::new())
+}
+
+/// Like [`compile`], but reports every syntax and semantic error instead of
+/// stopping at the first. After a syntax error the parser skips to the end of the
+/// statement; the analyzer reports at most one error per function body and
+/// top-level statement.
+///
+/// ```
+/// let source = "let a = ;\nlet b = 1;\nlet c = );\n";
+/// let errors = js_to_wasm_compiler::compile_all(source).unwrap_err();
+/// let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+/// assert_eq!(lines, [1, 3]);
+/// ```
+pub fn compile_all(source: &str) -> std::result::Result<String, Vec<CompilerError>> {
+    compile_with_options(
+        source,
+        &CompileOptions::default(),
+        Vec::new(),
+        &mut Vec::new(),
+    )
+}
+
+/// Like [`compile`], running `extra_passes` on the AST after the built-in
+/// optimizations and before code generation.
+///
+/// ```
+/// use js_to_wasm_compiler::ast::{Expr, Program, StmtKind};
+/// use js_to_wasm_compiler::{compile_with_passes, Pass, Result};
+///
+/// // Renames every parameter `n` to `count`
+/// struct RenameParam;
+///
+/// impl Pass for RenameParam {
+///     fn name(&self) -> &str {
+///         "rename-param"
+///     }
+///
+///     fn run(&mut self, program: &mut Program) -> Result<()> {
+///         for func in &mut program.functions {
+///             for param in &mut func.params {
+///                 if param == "n" {
+///                     *param = "count".to_string();
+///                 }
+///             }
+///             for stmt in &mut func.body {
+///                 if let StmtKind::Return(Expr::Identifier(name)) = &mut stmt.kind {
+///                     if name == "n" {
+///                         *name = "count".to_string();
+///                     }
+///                 }
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let source = "function id(n) { return n; } id(7);";
+/// let wat = compile_with_passes(source, vec![Box::new(RenameParam)]).unwrap();
+/// assert!(wat.contains("(param $count i32)"));
+/// assert!(wat.contains("local.get $count"));
+/// ```
+pub fn compile_with_passes(source: &str, extra_passes: Vec<Box<dyn Pass>>) -> Result<String> {
+    compile_with_options(
+        source,
+        &CompileOptions::default(),
+        extra_passes,
+        &mut Vec::new(),
+    )
+    .map_err(|mut errors| errors.remove(0))
+}
+
+/// The whole pipeline with `options`, running `extra_passes` as
+/// [`compile_with_passes`] does and reporting every error as [`compile_all`]
+/// does. Warnings are added to `warnings`, even when an error stops compilation.
+///
+/// ```
+/// use js_to_wasm_compiler::{compile_with_options, CompileOptions, Entry};
+///
+/// let options = CompileOptions {
+///     entry: Entry::Export,
+///     ..CompileOptions::default()
+/// };
+/// let mut warnings = Vec::new();
+/// let wat = compile_with_options("1 + 2;", &options, Vec::new(), &mut warnings).unwrap();
+/// assert!(wat.contains("(export \"main\")"));
+/// ```
+pub fn compile_with_options(
+    source: &str,
+    options: &CompileOptions,
+    extra_passes: Vec<Box<dyn Pass>>,
+    warnings: &mut Vec<CompilerWarning>,
+) -> std::result::Result<String, Vec<CompilerError>> {
+    let tokens = lex(source, options).map_err(|e| vec![e])?;
+    let program = build_program(tokens, options, extra_passes, warnings)?;
+    options.codegen().generate(&program).map_err(|e| vec![e])
+}
+
+/// The tokens of `source`, each with its line and column.
+pub fn lex(source: &str, options: &CompileOptions) -> Result<Vec<(Token, usize, usize)>> {
+    Lexer::new(source)
+        .reject_tabs(options.reject_tabs)
+        .tokenize()
+}
+
+/// Parses, checks and optimizes `tokens`, then runs `extra_passes`, giving the
+/// program that code generation takes. Every syntax and semantic error is
+/// reported; warnings are added to `warnings` even when errors stop it.
+pub fn build_program(
+    tokens: Vec<(Token, usize, usize)>,
+    options: &CompileOptions,
+    mut extra_passes: Vec<Box<dyn Pass>>,
+    warnings: &mut Vec<CompilerWarning>,
+) -> std::result::Result<Program, Vec<CompilerError>> {
+    let mut program = Parser::new(tokens).parse_program_all()?;
+
+    let mut analyzer = SemanticAnalyzer::new()
+        .allow(&options.allowed)
+        .enable(&options.enabled)
+        .max_params(options.max_params)
+        .start_result(matches!(options.entry, Entry::Start | Entry::Export));
+    let analyzed = analyzer.analyze_all(&mut program);
+    warnings.extend_from_slice(analyzer.warnings());
+    analyzed?;
+
+    optimize_program(&mut program, options.arithmetic, options.opt_level).map_err(|e| vec![e])?;
+    run_passes(&mut program, &mut extra_passes).map_err(|e| vec![e])?;
+    Ok(program)
+}
+
+/// Assembles WAT text (a core module or a component) into binary WebAssembly.
+///
+/// ```
+/// let wat = js_to_wasm_compiler::compile("function f() { return 1; }").unwrap();
+/// let wasm = js_to_wasm_compiler::assemble(&wat).unwrap();
+/// assert_eq!(&wasm[..8], b"\0asm\x01\0\0\0");
+/// ```
+pub fn assemble(wat: &str) -> Result<Vec<u8>> {
+    wat::parse_str(wat).map_err(|e| {
+        CompilerError::codegen(0, format!("Failed to assemble WAT: {}", e)).with_code(E_ASSEMBLE)
+    })
+}
@@ -1,54 +1,360 @@
-mod ast;
-mod codegen;
-mod error;
-mod lexer;
-mod optimizer;
-mod parser;
-mod semantic;
-
 use std::env;
 use std::fs;
+use std::io::{self, IsTerminal, Read};
+
+use js_to_wasm_compiler::ast::{ArithmeticMode, Program};
+use js_to_wasm_compiler::error::{explain, render};
+use js_to_wasm_compiler::{
+    assemble, build_program, lex, CompileOptions, CompilerError, CompilerWarning, Entry, OptLevel,
+    Result, WarningKind,
+};
+
+const USAGE: &str = "Usage: compiler [--no-tabs] [--list-exports] [--module-name <name>] [--component] [--wit] [--opcode-stats] [--report-stack-depth] [--emit <formats>] [-o <path>] [--allow <warning>] [--warn <warning>] [--max-params <n>] [--checked-arithmetic] [--no-tail-calls] [--wasi] [--entry start|wasi|export|none] [-O0|-O1|-O2] [--diagnostics text|json] [<input.js>]
+       compiler --explain <code>";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Emit {
+    Wat,
+    Wasm,
+    Tokens,
+    Wit,
+    Exports,
+    Ast,
+    AstJson,
+    OpcodeStats,
+    StackDepth,
+}
+
+impl Emit {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "wat" => Some(Emit::Wat),
+            "wasm" => Some(Emit::Wasm),
+            "tokens" => Some(Emit::Tokens),
+            "wit" => Some(Emit::Wit),
+            "exports" => Some(Emit::Exports),
+            "ast" => Some(Emit::Ast),
+            "ast-json" => Some(Emit::AstJson),
+            "opcode-stats" => Some(Emit::OpcodeStats),
+            "stack-depth" => Some(Emit::StackDepth),
+            _ => None,
+        }
+    }
+
+    // File suffix appended to the `-o` base name
+    fn extension(self) -> &'static str {
+        match self {
+            Emit::Wat => "wat",
+            Emit::Wasm => "wasm",
+            Emit::Tokens => "tokens.txt",
+            Emit::Wit => "wit",
+            Emit::Exports => "exports.txt",
+            Emit::Ast => "ast.txt",
+            Emit::AstJson => "ast.json",
+            Emit::OpcodeStats => "opcodes.txt",
+            Emit::StackDepth => "stack.txt",
+        }
+    }
+}
 
-use codegen::CodeGen;
-use lexer::Lexer;
-use optimizer::optimize_program;
-use parser::Parser;
-use semantic::SemanticAnalyzer;
+// How warnings and errors are written to stderr
+#[derive(Clone, Copy, PartialEq)]
+enum Diagnostics {
+    Text,
+    /// One JSON array of every warning and error, for editors
+    Json,
+}
+
+// The pipeline's settings, plus what only the CLI needs: what to write, where,
+// and how to report diagnostics
+struct Options {
+    compile: CompileOptions,
+    emit: Vec<Emit>,
+    output: Option<String>,
+    diagnostics: Diagnostics,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            compile: CompileOptions::default(),
+            emit: vec![Emit::Wat],
+            output: None,
+            diagnostics: Diagnostics::Text,
+        }
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: compiler <input.js>");
-        std::process::exit(1);
+    let mut options = Options::default();
+    let mut input_path = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-tabs" => options.compile.reject_tabs = true,
+            "--list-exports" => options.emit = vec![Emit::Exports],
+            "--module-name" => {
+                let name = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--module-name requires a value"));
+                options.compile.module_name = Some(name);
+            }
+            "--component" => options.compile.component = true,
+            "--checked-arithmetic" => options.compile.arithmetic = ArithmeticMode::Checked,
+            "--no-tail-calls" => options.compile.tail_calls = false,
+            "--wasi" => options.compile.wasi = true,
+            "--entry" => {
+                let mode = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--entry requires a value"));
+                options.compile.entry = parse_entry(&mode);
+            }
+            flag if flag.starts_with("--entry=") => {
+                options.compile.entry = parse_entry(&flag["--entry=".len()..]);
+            }
+            "-O0" => options.compile.opt_level = OptLevel::O0,
+            "-O1" => options.compile.opt_level = OptLevel::O1,
+            "-O2" => options.compile.opt_level = OptLevel::O2,
+            "--wit" => options.emit = vec![Emit::Wit],
+            "--opcode-stats" => options.emit = vec![Emit::OpcodeStats],
+            "--report-stack-depth" => options.emit = vec![Emit::StackDepth],
+            "--emit" => {
+                let formats = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--emit requires a value"));
+                options.emit = parse_emit_list(&formats);
+            }
+            flag if flag.starts_with("--emit=") => {
+                options.emit = parse_emit_list(&flag["--emit=".len()..]);
+            }
+            "-o" => {
+                let base = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("-o requires a value"));
+                options.output = Some(base);
+            }
+            "--allow" => {
+                let name = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--allow requires a value"));
+                let kind = WarningKind::from_name(&name)
+                    .unwrap_or_else(|| usage_error(&format!("Unknown warning: {}", name)));
+                options.compile.allowed.push(kind);
+            }
+            "--warn" => {
+                let name = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--warn requires a value"));
+                let kind = WarningKind::from_name(&name)
+                    .unwrap_or_else(|| usage_error(&format!("Unknown warning: {}", name)));
+                options.compile.enabled.push(kind);
+            }
+            "--diagnostics" => {
+                options.diagnostics = match args.next().as_deref() {
+                    Some("text") => Diagnostics::Text,
+                    Some("json") => Diagnostics::Json,
+                    _ => usage_error("--diagnostics requires text or json"),
+                }
+            }
+            "--explain" => {
+                let code = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("--explain requires an error code"));
+                match explain(&code) {
+                    Some(text) => {
+                        println!("{}: {}", code, text);
+                        return;
+                    }
+                    None => usage_error(&format!("Unknown error code: {}", code)),
+                }
+            }
+            "--max-params" => {
+                options.compile.max_params = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| usage_error("--max-params requires a number"));
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                usage_error(&format!("Unknown option: {}", flag))
+            }
+            path if input_path.is_none() => input_path = Some(path.to_string()),
+            _ => usage_error("Only one input file may be given"),
+        }
     }
 
-    let input = fs::read_to_string(&args[1]).expect("Failed to read input file");
+    if options.emit.len() > 1 && options.output.is_none() {
+        usage_error("Emitting several formats requires -o <base>");
+    }
+    if options.emit.contains(&Emit::Wasm) && options.output.is_none() {
+        usage_error("Emitting wasm requires -o <base>");
+    }
+    let input = read_input(input_path.as_deref());
 
-    let result = compile(&input);
+    let mut warnings = Vec::new();
+    let result = compile(&input, &options, &mut warnings);
+    let errors = result.as_ref().err().map_or(&[][..], Vec::as_slice);
+    report_diagnostics(&warnings, errors, &input, options.diagnostics);
 
     match result {
-        Ok(wat) => println!("{}", wat),
-        Err(e) => {
-            eprintln!("{}", e);
-            std::process::exit(1);
+        Ok(outputs) => match &options.output {
+            Some(base) => {
+                let single = outputs.len() == 1;
+                for (emit, bytes) in outputs {
+                    let path = output_path(base, emit, single);
+                    if let Err(e) = fs::write(&path, bytes) {
+                        eprintln!("Failed to write {}: {}", path, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            None => {
+                for (_, bytes) in outputs {
+                    print!("{}", String::from_utf8_lossy(&bytes));
+                }
+            }
+        },
+        Err(_) => std::process::exit(1),
+    }
+}
+
+// Warnings come before the errors that stopped compilation, if any. In text form
+// each error is followed by the source line it points at.
+fn report_diagnostics(
+    warnings: &[CompilerWarning],
+    errors: &[CompilerError],
+    source: &str,
+    format: Diagnostics,
+) {
+    match format {
+        Diagnostics::Text => {
+            for warning in warnings {
+                eprintln!("{}", warning);
+            }
+            for e in errors {
+                eprintln!("{}", render(e, source));
+            }
+        }
+        Diagnostics::Json => {
+            let entries: Vec<String> = warnings
+                .iter()
+                .map(CompilerWarning::to_json)
+                .chain(errors.iter().map(CompilerError::to_json))
+                .collect();
+            eprintln!("[{}]", entries.join(","));
         }
     }
 }
 
-fn compile(input: &str) -> error::Result<String> {
-    let mut lexer = Lexer::new(input);
-    let tokens = lexer.tokenize()?;
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    std::process::exit(1);
+}
 
-    let mut parser = Parser::new(tokens);
-    let mut program = parser.parse_program()?;
+// Reads the named file, or stdin when no path (or `-`) is given and input is piped
+fn read_input(path: Option<&str>) -> String {
+    let result = match path {
+        Some(path) if path != "-" => fs::read_to_string(path),
+        _ => {
+            if io::stdin().is_terminal() {
+                usage_error("Missing input file");
+            }
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {}", path.unwrap_or("stdin"), e);
+        std::process::exit(1);
+    })
+}
 
-    let mut analyzer = SemanticAnalyzer::new();
-    analyzer.analyze(&mut program)?;
+// `-o out` writes out.wat; with a single format, `-o out.wat` names the file as given
+fn output_path(base: &str, emit: Emit, single: bool) -> String {
+    let extension = format!(".{}", emit.extension());
+    if single && base.ends_with(&extension) {
+        base.to_string()
+    } else {
+        format!("{}{}", base, extension)
+    }
+}
 
-    optimize_program(&mut program);
+fn parse_entry(mode: &str) -> Entry {
+    match mode {
+        "start" => Entry::Start,
+        "wasi" => Entry::Wasi,
+        "export" => Entry::Export,
+        "none" => Entry::None,
+        _ => usage_error(&format!("Unknown entry mode: {}", mode)),
+    }
+}
 
-    let mut codegen = CodeGen::new();
-    let wat = codegen.generate(&program)?;
+fn parse_emit_list(formats: &str) -> Vec<Emit> {
+    let mut emit = Vec::new();
+    for name in formats.split(',') {
+        let format = Emit::parse(name.trim())
+            .unwrap_or_else(|| usage_error(&format!("Unknown emit format: {}", name)));
+        if !emit.contains(&format) {
+            emit.push(format);
+        }
+    }
+    emit
+}
+
+// Runs the library's pipeline once and renders every requested format from the
+// same AST. Text formats end with a newline; wasm is the raw module bytes.
+// Warnings are collected even when a later error stops compilation. The parser
+// and analyzer report every error they find; the other stages stop at the first.
+fn compile(
+    input: &str,
+    options: &Options,
+    warnings: &mut Vec<CompilerWarning>,
+) -> std::result::Result<Vec<(Emit, Vec<u8>)>, Vec<CompilerError>> {
+    let tokens = lex(input, &options.compile).map_err(|e| vec![e])?;
+
+    // One `line:col Token` per line; when that is all that was asked for, source
+    // that does not parse can still be dumped
+    let token_dump: Vec<String> = tokens
+        .iter()
+        .map(|(token, line, col)| format!("{}:{} {:?}", line, col, token))
+        .collect();
+    let token_dump = token_dump.join("\n");
+    if options.emit.iter().all(|&emit| emit == Emit::Tokens) {
+        return Ok(vec![(Emit::Tokens, (token_dump + "\n").into_bytes())]);
+    }
+
+    let program = build_program(tokens, &options.compile, Vec::new(), warnings)?;
+    render_outputs(program, &token_dump, options).map_err(|e| vec![e])
+}
+
+// Renders each requested format from the optimized program
+fn render_outputs(
+    program: Program,
+    token_dump: &str,
+    options: &Options,
+) -> Result<Vec<(Emit, Vec<u8>)>> {
+    let codegen = || options.compile.codegen();
+
+    let mut outputs = Vec::new();
+    for &emit in &options.emit {
+        let text = match emit {
+            Emit::Wat => codegen().generate(&program)?,
+            Emit::Wasm => {
+                outputs.push((emit, assemble(&codegen().generate(&program)?)?));
+                continue;
+            }
+            Emit::Tokens => token_dump.to_string(),
+            Emit::Wit => codegen().wit_world(&program)?,
+            Emit::Exports => codegen().list_exports(&program),
+            // The derived Debug output, indented one node per line
+            Emit::Ast => format!("{:#?}", program),
+            Emit::AstJson => program.to_json(),
+            Emit::OpcodeStats => codegen().opcode_stats(&program)?,
+            Emit::StackDepth => codegen().stack_depths(&program)?,
+        };
+        outputs.push((emit, (text + "\n").into_bytes()));
+    }
 
-    Ok(wat)
+    Ok(outputs)
 }
@@ -1,283 +1,1768 @@
 use crate::ast::*;
+use crate::error::{CompilerError, Result, E_CONST_OVERFLOW};
+use std::collections::{HashMap, HashSet};
 
-pub fn optimize_program(program: &mut Program) {
+/// How much [`optimize_program`] does, from the CLI's `-O0`, `-O1` and `-O2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    /// Nothing; the program is lowered as written
+    O0,
+    /// Constant folding, algebraic simplification, strength reduction and dead
+    /// code removal
+    O1,
+    /// Also constant propagation, unused binding removal, loop-invariant code
+    /// motion, common subexpression elimination and duplicate function merging.
+    /// The default.
+    O2,
+}
+
+/// Folds constants, removes dead code and unused bindings, computes repeated and
+/// loop-invariant expressions once and merges duplicate functions, as far as
+/// `level` allows. `arithmetic` must match the codegen mode: in checked mode a
+/// constant that overflows i32 is a compile error.
+pub fn optimize_program(
+    program: &mut Program,
+    arithmetic: ArithmeticMode,
+    level: OptLevel,
+) -> Result<()> {
+    if level == OptLevel::O0 {
+        return Ok(());
+    }
+    let full = level >= OptLevel::O2;
+    let mut optimizer = Optimizer::new(program, arithmetic, full);
+    // One pass can enable another: removing a dead branch that assigns a
+    // variable lets its literal be propagated. Repeat until a body stops changing.
+    // Bodies are moved through each pass rather than copied; a pass changed
+    // nothing when the body's fingerprint is the same afterwards.
     for func in &mut program.functions {
-        func.body = optimize_stmts(func.body.clone());
+        let mut before = fingerprint(&func.body);
+        for _ in 0..MAX_PASSES {
+            optimizer.enter_function(func);
+            func.body = optimizer.optimize_body(std::mem::take(&mut func.body))?;
+            let after = fingerprint(&func.body);
+            if after == before {
+                break;
+            }
+            before = after;
+        }
+        if full {
+            hoist_loop_invariants(&mut func.body, &func.params, arithmetic);
+            eliminate_common_subexpressions(&mut func.body, &func.params, arithmetic);
+        }
+    }
+    let mut before = fingerprint(&program.top_level);
+    for _ in 0..MAX_PASSES {
+        optimizer.enter_top_level(&program.top_level);
+        program.top_level = optimizer.optimize_body(std::mem::take(&mut program.top_level))?;
+        let after = fingerprint(&program.top_level);
+        if after == before {
+            break;
+        }
+        before = after;
+    }
+    if full {
+        hoist_loop_invariants(&mut program.top_level, &[], arithmetic);
+        eliminate_common_subexpressions(&mut program.top_level, &[], arithmetic);
+        merge_duplicate_functions(program);
+    }
+    Ok(())
+}
+
+// Passes over each body before giving up on reaching a fixpoint
+const MAX_PASSES: usize = 8;
+
+// Hashes the Debug form of a body as it is written out, without building the
+// string. Two bodies with the same fingerprint are taken to be the same tree.
+fn fingerprint(stmts: &[Stmt]) -> u64 {
+    use std::fmt::Write;
+    use std::hash::{DefaultHasher, Hasher};
+
+    struct HashWriter(DefaultHasher);
+    impl Write for HashWriter {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.write(s.as_bytes());
+            Ok(())
+        }
     }
-    program.top_level = optimize_stmts(program.top_level.clone());
+
+    let mut writer = HashWriter(DefaultHasher::new());
+    let _ = write!(writer, "{:?}", stmts);
+    writer.0.finish()
 }
 
-fn optimize_stmts(stmts: Vec<Stmt>) -> Vec<Stmt> {
-    let mut result = Vec::new();
+// Drops each function whose signature and body match an earlier one, redirecting
+// its calls to the earlier function, which keeps exporting the dropped name.
+// Redirecting can make more bodies identical, so repeat until nothing merges.
+fn merge_duplicate_functions(program: &mut Program) {
+    while let Some((keep, drop)) = find_duplicate(&program.functions) {
+        let duplicate = program.functions.remove(drop);
+        let canonical = program.functions[keep].name.clone();
+
+        for func in &mut program.functions {
+            redirect_calls(&mut func.body, &duplicate.name, &canonical);
+        }
+        redirect_calls(&mut program.top_level, &duplicate.name, &canonical);
+
+        let aliases = &mut program.functions[keep].aliases;
+        aliases.push(duplicate.name);
+        aliases.extend(duplicate.aliases);
+    }
+}
 
+fn find_duplicate(functions: &[Function]) -> Option<(usize, usize)> {
+    for (j, later) in functions.iter().enumerate() {
+        for (i, earlier) in functions[..j].iter().enumerate() {
+            if same_function(earlier, later) {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+// Structural equality ignoring function names and source lines
+fn same_function(a: &Function, b: &Function) -> bool {
+    a.params == b.params
+        && a.param_types == b.param_types
+        && a.return_type == b.return_type
+        && same_stmts(&a.body, &b.body)
+}
+
+fn same_stmts(a: &[Stmt], b: &[Stmt]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| same_stmt(x, y))
+}
+
+fn same_stmt(a: &Stmt, b: &Stmt) -> bool {
+    match (&a.kind, &b.kind) {
+        (StmtKind::Let(n1, e1), StmtKind::Let(n2, e2))
+        | (StmtKind::Const(n1, Some(e1)), StmtKind::Const(n2, Some(e2)))
+        | (StmtKind::Assign(n1, e1), StmtKind::Assign(n2, e2)) => n1 == n2 && same_expr(e1, e2),
+        (StmtKind::If(c1, t1, e1), StmtKind::If(c2, t2, e2)) => {
+            same_expr(c1, c2) && same_stmt(t1, t2) && same_opt_stmt(e1, e2)
+        }
+        (StmtKind::While(c1, b1), StmtKind::While(c2, b2))
+        | (StmtKind::DoWhile(b1, c1), StmtKind::DoWhile(b2, c2)) => {
+            same_expr(c1, c2) && same_stmt(b1, b2)
+        }
+        (StmtKind::For(i1, c1, n1, b1), StmtKind::For(i2, c2, n2, b2)) => {
+            same_opt_stmt(i1, i2)
+                && match (c1, c2) {
+                    (Some(c1), Some(c2)) => same_expr(c1, c2),
+                    (None, None) => true,
+                    _ => false,
+                }
+                && same_opt_stmt(n1, n2)
+                && same_stmt(b1, b2)
+        }
+        (StmtKind::Switch(d1, c1, f1), StmtKind::Switch(d2, c2, f2)) => {
+            same_expr(d1, d2)
+                && c1.len() == c2.len()
+                && c1
+                    .iter()
+                    .zip(c2)
+                    .all(|((l1, b1), (l2, b2))| same_expr(l1, l2) && same_stmts(b1, b2))
+                && match (f1, f2) {
+                    (Some(f1), Some(f2)) => same_stmts(f1, f2),
+                    (None, None) => true,
+                    _ => false,
+                }
+        }
+        (StmtKind::Block(s1), StmtKind::Block(s2)) => same_stmts(s1, s2),
+        (StmtKind::Return(e1), StmtKind::Return(e2)) | (StmtKind::Expr(e1), StmtKind::Expr(e2)) => {
+            same_expr(e1, e2)
+        }
+        (StmtKind::Break, StmtKind::Break) | (StmtKind::Continue, StmtKind::Continue) => true,
+        _ => false,
+    }
+}
+
+fn same_opt_stmt(a: &Option<Box<Stmt>>, b: &Option<Box<Stmt>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => same_stmt(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn same_expr(a: &Expr, b: &Expr) -> bool {
+    match (a, b) {
+        (Expr::Number(x), Expr::Number(y)) => x == y,
+        (Expr::Null, Expr::Null) => true,
+        // Compare bits so 0.0 and -0.0 stay distinct
+        (Expr::NumberF32(x), Expr::NumberF32(y)) => x.to_bits() == y.to_bits(),
+        (Expr::StringLit(x), Expr::StringLit(y)) | (Expr::Identifier(x), Expr::Identifier(y)) => {
+            x == y
+        }
+        (Expr::Binary(l1, o1, r1), Expr::Binary(l2, o2, r2)) => {
+            o1 == o2 && same_expr(l1, l2) && same_expr(r1, r2)
+        }
+        (Expr::Unary(o1, e1), Expr::Unary(o2, e2)) => o1 == o2 && same_expr(e1, e2),
+        (Expr::Call(n1, a1), Expr::Call(n2, a2)) => {
+            n1 == n2 && a1.len() == a2.len() && a1.iter().zip(a2).all(|(x, y)| same_expr(x, y))
+        }
+        (Expr::Logical(l1, o1, r1), Expr::Logical(l2, o2, r2)) => {
+            o1 == o2 && same_expr(l1, l2) && same_expr(r1, r2)
+        }
+        (Expr::Update(o1, n1), Expr::Update(o2, n2)) => o1 == o2 && n1 == n2,
+        (Expr::Assign(n1, v1), Expr::Assign(n2, v2)) => n1 == n2 && same_expr(v1, v2),
+        (Expr::Ternary(c1, t1, e1), Expr::Ternary(c2, t2, e2)) => {
+            same_expr(c1, c2) && same_expr(t1, t2) && same_expr(e1, e2)
+        }
+        // Two array literals are two different arrays
+        (Expr::Index(a1, i1), Expr::Index(a2, i2)) => same_expr(a1, a2) && same_expr(i1, i2),
+        (Expr::Member(o1, p1), Expr::Member(o2, p2)) => p1 == p2 && same_expr(o1, o2),
+        _ => false,
+    }
+}
+
+fn redirect_calls(stmts: &mut [Stmt], from: &str, to: &str) {
     for stmt in stmts {
-        let optimized = optimize_stmt(stmt);
+        redirect_calls_stmt(stmt, from, to);
+    }
+}
 
-        // Check if this statement is a return
-        let is_return = matches!(optimized.kind, StmtKind::Return(_));
+fn redirect_calls_stmt(stmt: &mut Stmt, from: &str, to: &str) {
+    match &mut stmt.kind {
+        StmtKind::Let(_, expr)
+        | StmtKind::Const(_, Some(expr))
+        | StmtKind::Assign(_, expr)
+        | StmtKind::Return(expr)
+        | StmtKind::Expr(expr) => redirect_calls_expr(expr, from, to),
+        StmtKind::If(cond, then_branch, else_branch) => {
+            redirect_calls_expr(cond, from, to);
+            redirect_calls_stmt(then_branch, from, to);
+            if let Some(else_branch) = else_branch {
+                redirect_calls_stmt(else_branch, from, to);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            redirect_calls_expr(cond, from, to);
+            redirect_calls_stmt(body, from, to);
+        }
+        StmtKind::For(init, cond, incr, body) => {
+            if let Some(init) = init {
+                redirect_calls_stmt(init, from, to);
+            }
+            if let Some(cond) = cond {
+                redirect_calls_expr(cond, from, to);
+            }
+            if let Some(incr) = incr {
+                redirect_calls_stmt(incr, from, to);
+            }
+            redirect_calls_stmt(body, from, to);
+        }
+        StmtKind::Switch(disc, cases, default) => {
+            redirect_calls_expr(disc, from, to);
+            for (label, body) in cases {
+                redirect_calls_expr(label, from, to);
+                redirect_calls(body, from, to);
+            }
+            if let Some(body) = default {
+                redirect_calls(body, from, to);
+            }
+        }
+        StmtKind::Block(stmts) => redirect_calls(stmts, from, to),
+        StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+fn redirect_calls_expr(expr: &mut Expr, from: &str, to: &str) {
+    match expr {
+        Expr::Call(name, args) => {
+            if name == from {
+                *name = to.to_string();
+            }
+            for arg in args {
+                redirect_calls_expr(arg, from, to);
+            }
+        }
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            redirect_calls_expr(left, from, to);
+            redirect_calls_expr(right, from, to);
+        }
+        Expr::Unary(_, operand) | Expr::Assign(_, operand) | Expr::Member(operand, _) => {
+            redirect_calls_expr(operand, from, to)
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            redirect_calls_expr(cond, from, to);
+            redirect_calls_expr(then_expr, from, to);
+            redirect_calls_expr(else_expr, from, to);
+        }
+        Expr::ArrayLit(elements) => {
+            for element in elements {
+                redirect_calls_expr(element, from, to);
+            }
+        }
+        Expr::Index(array, index) => {
+            redirect_calls_expr(array, from, to);
+            redirect_calls_expr(index, from, to);
+        }
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_)
+        | Expr::Update(..) => {}
+    }
+}
+
+// Names whose value can change after their declaration: assigned, updated with
+// `++`/`--`, or declared more than once (shadowing the semantic analyzer has not
+// resolved), so a literal initializer says nothing about later uses
+fn unstable_names(stmts: &[Stmt]) -> HashSet<String> {
+    let mut declared = HashSet::new();
+    let mut unstable = HashSet::new();
+    for stmt in stmts {
+        collect_unstable_stmt(stmt, &mut declared, &mut unstable);
+    }
+    unstable
+}
 
-        result.push(optimized);
+fn collect_unstable_stmt(
+    stmt: &Stmt,
+    declared: &mut HashSet<String>,
+    unstable: &mut HashSet<String>,
+) {
+    match &stmt.kind {
+        StmtKind::Let(name, expr) | StmtKind::Const(name, Some(expr)) => {
+            collect_unstable_expr(expr, unstable);
+            if !declared.insert(name.clone()) {
+                unstable.insert(name.clone());
+            }
+        }
+        StmtKind::Assign(name, expr) => {
+            collect_unstable_expr(expr, unstable);
+            unstable.insert(name.clone());
+        }
+        StmtKind::Return(expr) | StmtKind::Expr(expr) => collect_unstable_expr(expr, unstable),
+        StmtKind::If(cond, then_branch, else_branch) => {
+            collect_unstable_expr(cond, unstable);
+            collect_unstable_stmt(then_branch, declared, unstable);
+            if let Some(else_branch) = else_branch {
+                collect_unstable_stmt(else_branch, declared, unstable);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            collect_unstable_expr(cond, unstable);
+            collect_unstable_stmt(body, declared, unstable);
+        }
+        StmtKind::For(init, cond, incr, body) => {
+            if let Some(init) = init {
+                collect_unstable_stmt(init, declared, unstable);
+            }
+            if let Some(cond) = cond {
+                collect_unstable_expr(cond, unstable);
+            }
+            if let Some(incr) = incr {
+                collect_unstable_stmt(incr, declared, unstable);
+            }
+            collect_unstable_stmt(body, declared, unstable);
+        }
+        StmtKind::Switch(disc, cases, default) => {
+            collect_unstable_expr(disc, unstable);
+            for (label, body) in cases {
+                collect_unstable_expr(label, unstable);
+                for stmt in body {
+                    collect_unstable_stmt(stmt, declared, unstable);
+                }
+            }
+            for stmt in default.iter().flatten() {
+                collect_unstable_stmt(stmt, declared, unstable);
+            }
+        }
+        StmtKind::Block(stmts) => {
+            for stmt in stmts {
+                collect_unstable_stmt(stmt, declared, unstable);
+            }
+        }
+        StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
 
-        // Stop processing after return 
-        if is_return {
+fn collect_unstable_expr(expr: &Expr, unstable: &mut HashSet<String>) {
+    match expr {
+        Expr::Update(_, name) => {
+            unstable.insert(name.clone());
+        }
+        Expr::Assign(name, value) => {
+            collect_unstable_expr(value, unstable);
+            unstable.insert(name.clone());
+        }
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            collect_unstable_expr(left, unstable);
+            collect_unstable_expr(right, unstable);
+        }
+        Expr::Unary(_, operand) | Expr::Member(operand, _) => {
+            collect_unstable_expr(operand, unstable)
+        }
+        Expr::Call(_, args) => {
+            for arg in args {
+                collect_unstable_expr(arg, unstable);
+            }
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            collect_unstable_expr(cond, unstable);
+            collect_unstable_expr(then_expr, unstable);
+            collect_unstable_expr(else_expr, unstable);
+        }
+        Expr::ArrayLit(elements) => {
+            for element in elements {
+                collect_unstable_expr(element, unstable);
+            }
+        }
+        Expr::Index(array, index) => {
+            collect_unstable_expr(array, unstable);
+            collect_unstable_expr(index, unstable);
+        }
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_) => {}
+    }
+}
+
+// Drops `let`/`const` bindings whose name is never read, assigned or updated,
+// when computing the initializer can't call a function or trap. Dropping one can
+// leave another unread (`let a = x; let b = a;`), so repeat until nothing changes.
+fn remove_dead_bindings(stmts: &mut Vec<Stmt>, arithmetic: ArithmeticMode) {
+    loop {
+        let mut referenced = HashSet::new();
+        for stmt in stmts.iter() {
+            collect_references_stmt(stmt, &mut referenced);
+        }
+        if !remove_unreferenced(stmts, &referenced, arithmetic) {
             break;
         }
     }
+}
 
-    result
+// Returns whether anything was removed
+fn remove_unreferenced(
+    stmts: &mut Vec<Stmt>,
+    referenced: &HashSet<String>,
+    arithmetic: ArithmeticMode,
+) -> bool {
+    let before = stmts.len();
+    stmts.retain(|stmt| !is_dead_binding(stmt, referenced, arithmetic));
+    let mut removed = stmts.len() != before;
+    for stmt in stmts.iter_mut() {
+        removed |= remove_unreferenced_nested(stmt, referenced, arithmetic);
+    }
+    removed
 }
 
-fn optimize_stmt(stmt: Stmt) -> Stmt {
-    let kind = match stmt.kind {
-        StmtKind::Let(name, expr) => StmtKind::Let(name, fold_expr(expr)),
-        StmtKind::Const(name, expr) => StmtKind::Const(name, fold_expr(expr)),
-        StmtKind::Assign(name, expr) => StmtKind::Assign(name, fold_expr(expr)),
-        StmtKind::If(cond, then_branch, else_branch) => {
-            let cond = fold_expr(cond);
+fn remove_unreferenced_nested(
+    stmt: &mut Stmt,
+    referenced: &HashSet<String>,
+    arithmetic: ArithmeticMode,
+) -> bool {
+    match &mut stmt.kind {
+        StmtKind::Block(stmts) => remove_unreferenced(stmts, referenced, arithmetic),
+        StmtKind::If(_, then_branch, else_branch) => {
+            let mut removed = remove_unreferenced_boxed(then_branch, referenced, arithmetic);
+            if let Some(else_branch) = else_branch {
+                removed |= remove_unreferenced_boxed(else_branch, referenced, arithmetic);
+            }
+            removed
+        }
+        StmtKind::While(_, body) | StmtKind::DoWhile(body, _) | StmtKind::For(_, _, _, body) => {
+            remove_unreferenced_boxed(body, referenced, arithmetic)
+        }
+        StmtKind::Switch(_, cases, default) => {
+            let mut removed = false;
+            for body in cases.iter_mut().map(|(_, body)| body).chain(default) {
+                removed |= remove_unreferenced(body, referenced, arithmetic);
+            }
+            removed
+        }
+        _ => false,
+    }
+}
+
+// A dead binding standing alone as a branch or loop body becomes an empty block
+fn remove_unreferenced_boxed(
+    stmt: &mut Stmt,
+    referenced: &HashSet<String>,
+    arithmetic: ArithmeticMode,
+) -> bool {
+    if is_dead_binding(stmt, referenced, arithmetic) {
+        stmt.kind = StmtKind::Block(vec![]);
+        return true;
+    }
+    remove_unreferenced_nested(stmt, referenced, arithmetic)
+}
+
+fn is_dead_binding(stmt: &Stmt, referenced: &HashSet<String>, arithmetic: ArithmeticMode) -> bool {
+    match &stmt.kind {
+        StmtKind::Let(name, init) | StmtKind::Const(name, Some(init)) => {
+            !referenced.contains(name) && !has_side_effects(init, arithmetic)
+        }
+        _ => false,
+    }
+}
 
-            // Dead code: if (0) or if (0.0) -> remove
-            let is_false = match &cond {
-                Expr::Number(n) => *n == 0,
-                Expr::NumberF32(f) => *f == 0.0,
+// Whether evaluating `expr` can do more than produce a value: calls, updates,
+// and operations that may trap (division, overflow in checked mode, and
+// indexing past the end of an array)
+fn has_side_effects(expr: &Expr, arithmetic: ArithmeticMode) -> bool {
+    match expr {
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_) => false,
+        Expr::Call(..) | Expr::Update(..) | Expr::Assign(..) => true,
+        Expr::Binary(left, op, right) => {
+            let may_trap = match op {
+                BinOp::Div | BinOp::Mod => !matches!(**right, Expr::Number(n) if n != 0 && n != -1),
+                BinOp::Add | BinOp::Sub | BinOp::Mul => arithmetic == ArithmeticMode::Checked,
                 _ => false,
             };
+            may_trap || has_side_effects(left, arithmetic) || has_side_effects(right, arithmetic)
+        }
+        Expr::Unary(op, operand) => {
+            (*op == UnaryOp::Neg && arithmetic == ArithmeticMode::Checked)
+                || has_side_effects(operand, arithmetic)
+        }
+        Expr::Logical(left, _, right) => {
+            has_side_effects(left, arithmetic) || has_side_effects(right, arithmetic)
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            has_side_effects(cond, arithmetic)
+                || has_side_effects(then_expr, arithmetic)
+                || has_side_effects(else_expr, arithmetic)
+        }
+        Expr::ArrayLit(elements) => elements.iter().any(|e| has_side_effects(e, arithmetic)),
+        Expr::Index(..) => true,
+        Expr::Member(object, _) => has_side_effects(object, arithmetic),
+    }
+}
 
-            if is_false {
-                // if (false) - use else branch or empty block
-                return match else_branch {
-                    Some(eb) => optimize_stmt(*eb),
-                    None => Stmt {
-                        kind: StmtKind::Block(vec![]),
-                        line: stmt.line,
-                    },
-                };
+// Names the temporaries in `stmts` must not reuse
+fn taken_names(stmts: &[Stmt], params: &[String]) -> HashSet<String> {
+    let mut taken: HashSet<String> = params.iter().cloned().collect();
+    for stmt in stmts {
+        collect_references_stmt(stmt, &mut taken);
+        collect_unstable_stmt(stmt, &mut taken, &mut HashSet::new());
+    }
+    taken
+}
+
+// `{prefix}_0`, `{prefix}_1`..., skipping any name the body already uses
+fn fresh_name(prefix: &str, taken: &mut HashSet<String>, next: &mut usize) -> String {
+    loop {
+        let name = format!("{}_{}", prefix, next);
+        *next += 1;
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+// Computes a pure expression that a statement evaluates twice only once, in a
+// new `let` placed just before the statement. Only what a statement evaluates
+// once, up front, is searched: loop conditions and `for` updates run again each
+// iteration. An expression that assigns or updates a variable is left alone,
+// since the value could change between the two uses.
+fn eliminate_common_subexpressions(
+    stmts: &mut Vec<Stmt>,
+    params: &[String],
+    arithmetic: ArithmeticMode,
+) {
+    let mut cse = Cse {
+        taken: taken_names(stmts, params),
+        next: 0,
+        arithmetic,
+    };
+    cse.stmts(stmts);
+}
+
+struct Cse {
+    taken: HashSet<String>,
+    next: usize,
+    arithmetic: ArithmeticMode,
+}
+
+impl Cse {
+    fn stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        for mut stmt in std::mem::take(stmts) {
+            let hoisted = self.stmt(&mut stmt);
+            stmts.extend(hoisted);
+            stmts.push(stmt);
+        }
+    }
+
+    // A branch or loop body that is a single statement becomes a block when it
+    // needs temporaries of its own
+    fn boxed(&mut self, stmt: &mut Stmt) {
+        let mut hoisted = self.stmt(stmt);
+        if !hoisted.is_empty() {
+            let kind = std::mem::replace(&mut stmt.kind, StmtKind::Block(Vec::new()));
+            hoisted.push(Stmt {
+                kind,
+                line: stmt.line,
+            });
+            stmt.kind = StmtKind::Block(hoisted);
+        }
+    }
+
+    // Rewrites what the statement evaluates up front and returns the `let`s that
+    // must precede it; nested bodies are rewritten in place
+    fn stmt(&mut self, stmt: &mut Stmt) -> Vec<Stmt> {
+        let line = stmt.line;
+        match &mut stmt.kind {
+            StmtKind::Let(_, expr)
+            | StmtKind::Const(_, Some(expr))
+            | StmtKind::Assign(_, expr)
+            | StmtKind::Return(expr)
+            | StmtKind::Expr(expr) => self.expr(expr, line),
+            StmtKind::If(cond, then_branch, else_branch) => {
+                let hoisted = self.expr(cond, line);
+                self.boxed(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.boxed(else_branch);
+                }
+                hoisted
+            }
+            StmtKind::Switch(disc, cases, default) => {
+                let hoisted = self.expr(disc, line);
+                for (_, body) in cases.iter_mut() {
+                    self.stmts(body);
+                }
+                if let Some(default) = default {
+                    self.stmts(default);
+                }
+                hoisted
+            }
+            StmtKind::While(_, body)
+            | StmtKind::DoWhile(body, _)
+            | StmtKind::For(_, _, _, body) => {
+                self.boxed(body);
+                Vec::new()
             }
+            StmtKind::Block(inner) => {
+                self.stmts(inner);
+                Vec::new()
+            }
+            StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => Vec::new(),
+        }
+    }
 
-            // if (non-zero constant) -> keep then only
-            let is_true = match &cond {
-                Expr::Number(n) => *n != 0,
-                Expr::NumberF32(f) => *f != 0.0,
-                _ => false,
-            };
+    fn expr(&mut self, expr: &mut Expr, line: usize) -> Vec<Stmt> {
+        let mut hoisted = Vec::new();
+        let mut assigned = HashSet::new();
+        collect_unstable_expr(expr, &mut assigned);
+        if !assigned.is_empty() {
+            return hoisted;
+        }
+        while let Some(common) = self.repeated(expr) {
+            let name = self.fresh();
+            replace_expr(expr, &common, &name);
+            hoisted.push(Stmt {
+                kind: StmtKind::Let(name, common),
+                line,
+            });
+        }
+        hoisted
+    }
+
+    // The outermost pure operation that appears more than once
+    fn repeated(&self, expr: &Expr) -> Option<Expr> {
+        let mut candidates = Vec::new();
+        collect_candidates(expr, self.arithmetic, &mut candidates);
+        candidates
+            .iter()
+            .find(|&&candidate| {
+                candidates
+                    .iter()
+                    .filter(|&&other| same_expr(candidate, other))
+                    .count()
+                    > 1
+            })
+            .map(|&candidate| candidate.clone())
+    }
+
+    fn fresh(&mut self) -> String {
+        fresh_name("_cse", &mut self.taken, &mut self.next)
+    }
+}
 
-            if is_true {
-                return optimize_stmt(*then_branch);
+// Computes a pure expression that a loop evaluates on every iteration, but
+// whose variables the loop never assigns, updates or declares, once in a new
+// `let` placed just before the loop
+fn hoist_loop_invariants(stmts: &mut Vec<Stmt>, params: &[String], arithmetic: ArithmeticMode) {
+    let mut licm = Licm {
+        taken: taken_names(stmts, params),
+        next: 0,
+        arithmetic,
+    };
+    licm.stmts(stmts);
+}
+
+struct Licm {
+    taken: HashSet<String>,
+    next: usize,
+    arithmetic: ArithmeticMode,
+}
+
+impl Licm {
+    fn stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        for mut stmt in std::mem::take(stmts) {
+            let hoisted = self.stmt(&mut stmt);
+            stmts.extend(hoisted);
+            stmts.push(stmt);
+        }
+    }
+
+    // A loop that is a branch or loop body on its own becomes a block when
+    // something is hoisted out of it
+    fn boxed(&mut self, stmt: &mut Stmt) {
+        let mut hoisted = self.stmt(stmt);
+        if !hoisted.is_empty() {
+            let kind = std::mem::replace(&mut stmt.kind, StmtKind::Block(Vec::new()));
+            hoisted.push(Stmt {
+                kind,
+                line: stmt.line,
+            });
+            stmt.kind = StmtKind::Block(hoisted);
+        }
+    }
+
+    // Hoists out of a loop, then out of the loops nested in it, and returns the
+    // `let`s that must precede the statement
+    fn stmt(&mut self, stmt: &mut Stmt) -> Vec<Stmt> {
+        let hoisted = match stmt.kind {
+            StmtKind::While(..) | StmtKind::DoWhile(..) | StmtKind::For(..) => self.hoist(stmt),
+            _ => Vec::new(),
+        };
+        match &mut stmt.kind {
+            StmtKind::If(_, then_branch, else_branch) => {
+                self.boxed(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.boxed(else_branch);
+                }
+            }
+            StmtKind::While(_, body)
+            | StmtKind::DoWhile(body, _)
+            | StmtKind::For(_, _, _, body) => self.boxed(body),
+            StmtKind::Switch(_, cases, default) => {
+                for (_, body) in cases.iter_mut() {
+                    self.stmts(body);
+                }
+                if let Some(default) = default {
+                    self.stmts(default);
+                }
             }
+            StmtKind::Block(inner) => self.stmts(inner),
+            _ => {}
+        }
+        hoisted
+    }
+
+    fn hoist(&mut self, looped: &mut Stmt) -> Vec<Stmt> {
+        // A name declared inside the loop is a new value each iteration
+        let mut declared = HashSet::new();
+        let mut variant = HashSet::new();
+        collect_unstable_stmt(looped, &mut declared, &mut variant);
+        variant.extend(declared);
 
-            let then_branch = Box::new(optimize_stmt(*then_branch));
-            let else_branch = else_branch.map(|eb| Box::new(optimize_stmt(*eb)));
-            StmtKind::If(cond, then_branch, else_branch)
+        let mut hoisted = Vec::new();
+        while let Some(invariant) = self.invariant(looped, &variant) {
+            let name = self.fresh();
+            replace_stmt(looped, &invariant, &name);
+            hoisted.push(Stmt {
+                kind: StmtKind::Let(name, invariant),
+                line: looped.line,
+            });
         }
-        StmtKind::While(cond, body) => {
-            let cond = fold_expr(cond);
+        hoisted
+    }
 
-            // Dead code: while (0) or while (0.0) -> remove entirely
-            let is_false = match &cond {
-                Expr::Number(n) => *n == 0,
-                Expr::NumberF32(f) => *f == 0.0,
-                _ => false,
-            };
+    // The first pure operation the loop repeats that reads no variant name. A
+    // `for` initializer runs once already, so it is not searched.
+    fn invariant(&self, looped: &Stmt, variant: &HashSet<String>) -> Option<Expr> {
+        let mut candidates = Vec::new();
+        match &looped.kind {
+            StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+                collect_candidates(cond, self.arithmetic, &mut candidates);
+                collect_candidates_stmt(body, self.arithmetic, &mut candidates);
+            }
+            StmtKind::For(_, cond, incr, body) => {
+                if let Some(cond) = cond {
+                    collect_candidates(cond, self.arithmetic, &mut candidates);
+                }
+                if let Some(incr) = incr {
+                    collect_candidates_stmt(incr, self.arithmetic, &mut candidates);
+                }
+                collect_candidates_stmt(body, self.arithmetic, &mut candidates);
+            }
+            _ => {}
+        }
+        candidates
+            .into_iter()
+            .find(|candidate| {
+                let mut reads = HashSet::new();
+                collect_references_expr(candidate, &mut reads);
+                reads.is_disjoint(variant)
+            })
+            .cloned()
+    }
 
-            if is_false {
-                return Stmt {
-                    kind: StmtKind::Block(vec![]),
-                    line: stmt.line,
-                };
+    fn fresh(&mut self) -> String {
+        fresh_name("_licm", &mut self.taken, &mut self.next)
+    }
+}
+
+// Pure operations in `expr`, outermost first. Literals and variables are
+// as cheap to repeat as a `local.get`.
+fn collect_candidates<'a>(
+    expr: &'a Expr,
+    arithmetic: ArithmeticMode,
+    candidates: &mut Vec<&'a Expr>,
+) {
+    let operation = matches!(
+        expr,
+        Expr::Binary(..)
+            | Expr::Unary(..)
+            | Expr::Logical(..)
+            | Expr::Ternary(..)
+            | Expr::Member(..)
+    );
+    if operation && !has_side_effects(expr, arithmetic) {
+        candidates.push(expr);
+    }
+    match expr {
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            collect_candidates(left, arithmetic, candidates);
+            collect_candidates(right, arithmetic, candidates);
+        }
+        Expr::Unary(_, operand) | Expr::Member(operand, _) | Expr::Assign(_, operand) => {
+            collect_candidates(operand, arithmetic, candidates)
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            collect_candidates(cond, arithmetic, candidates);
+            collect_candidates(then_expr, arithmetic, candidates);
+            collect_candidates(else_expr, arithmetic, candidates);
+        }
+        Expr::Call(_, args) | Expr::ArrayLit(args) => {
+            for arg in args {
+                collect_candidates(arg, arithmetic, candidates);
+            }
+        }
+        Expr::Index(array, index) => {
+            collect_candidates(array, arithmetic, candidates);
+            collect_candidates(index, arithmetic, candidates);
+        }
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_)
+        | Expr::Update(..) => {}
+    }
+}
+
+fn collect_candidates_stmt<'a>(
+    stmt: &'a Stmt,
+    arithmetic: ArithmeticMode,
+    candidates: &mut Vec<&'a Expr>,
+) {
+    match &stmt.kind {
+        StmtKind::Let(_, expr)
+        | StmtKind::Const(_, Some(expr))
+        | StmtKind::Assign(_, expr)
+        | StmtKind::Return(expr)
+        | StmtKind::Expr(expr) => collect_candidates(expr, arithmetic, candidates),
+        StmtKind::If(cond, then_branch, else_branch) => {
+            collect_candidates(cond, arithmetic, candidates);
+            collect_candidates_stmt(then_branch, arithmetic, candidates);
+            if let Some(else_branch) = else_branch {
+                collect_candidates_stmt(else_branch, arithmetic, candidates);
             }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            collect_candidates(cond, arithmetic, candidates);
+            collect_candidates_stmt(body, arithmetic, candidates);
+        }
+        StmtKind::For(init, cond, incr, body) => {
+            if let Some(init) = init {
+                collect_candidates_stmt(init, arithmetic, candidates);
+            }
+            if let Some(cond) = cond {
+                collect_candidates(cond, arithmetic, candidates);
+            }
+            if let Some(incr) = incr {
+                collect_candidates_stmt(incr, arithmetic, candidates);
+            }
+            collect_candidates_stmt(body, arithmetic, candidates);
+        }
+        StmtKind::Switch(disc, cases, default) => {
+            collect_candidates(disc, arithmetic, candidates);
+            for stmt in cases
+                .iter()
+                .flat_map(|(_, body)| body)
+                .chain(default.iter().flatten())
+            {
+                collect_candidates_stmt(stmt, arithmetic, candidates);
+            }
+        }
+        StmtKind::Block(stmts) => {
+            for stmt in stmts {
+                collect_candidates_stmt(stmt, arithmetic, candidates);
+            }
+        }
+        StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+// Replaces every occurrence of `target` in `expr` with a read of `name`
+fn replace_expr(expr: &mut Expr, target: &Expr, name: &str) {
+    if same_expr(expr, target) {
+        *expr = Expr::Identifier(name.to_string());
+        return;
+    }
+    match expr {
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            replace_expr(left, target, name);
+            replace_expr(right, target, name);
+        }
+        Expr::Unary(_, operand) | Expr::Member(operand, _) | Expr::Assign(_, operand) => {
+            replace_expr(operand, target, name)
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            replace_expr(cond, target, name);
+            replace_expr(then_expr, target, name);
+            replace_expr(else_expr, target, name);
+        }
+        Expr::Call(_, args) | Expr::ArrayLit(args) => {
+            for arg in args {
+                replace_expr(arg, target, name);
+            }
+        }
+        Expr::Index(array, index) => {
+            replace_expr(array, target, name);
+            replace_expr(index, target, name);
+        }
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_)
+        | Expr::Update(..) => {}
+    }
+}
 
-            let body = Box::new(optimize_stmt(*body));
-            StmtKind::While(cond, body)
+fn replace_stmt(stmt: &mut Stmt, target: &Expr, name: &str) {
+    match &mut stmt.kind {
+        StmtKind::Let(_, expr)
+        | StmtKind::Const(_, Some(expr))
+        | StmtKind::Assign(_, expr)
+        | StmtKind::Return(expr)
+        | StmtKind::Expr(expr) => replace_expr(expr, target, name),
+        StmtKind::If(cond, then_branch, else_branch) => {
+            replace_expr(cond, target, name);
+            replace_stmt(then_branch, target, name);
+            if let Some(else_branch) = else_branch {
+                replace_stmt(else_branch, target, name);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            replace_expr(cond, target, name);
+            replace_stmt(body, target, name);
         }
         StmtKind::For(init, cond, incr, body) => {
-            let init = init.map(|i| Box::new(optimize_stmt(*i)));
-            let cond = cond.map(fold_expr);
-            let incr = incr.map(|i| Box::new(optimize_stmt(*i)));
+            if let Some(init) = init {
+                replace_stmt(init, target, name);
+            }
+            if let Some(cond) = cond {
+                replace_expr(cond, target, name);
+            }
+            if let Some(incr) = incr {
+                replace_stmt(incr, target, name);
+            }
+            replace_stmt(body, target, name);
+        }
+        StmtKind::Switch(disc, cases, default) => {
+            replace_expr(disc, target, name);
+            for stmt in cases
+                .iter_mut()
+                .flat_map(|(_, body)| body)
+                .chain(default.iter_mut().flatten())
+            {
+                replace_stmt(stmt, target, name);
+            }
+        }
+        StmtKind::Block(stmts) => {
+            for stmt in stmts {
+                replace_stmt(stmt, target, name);
+            }
+        }
+        StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+// Every name read, assigned or updated anywhere in the statement
+fn collect_references_stmt(stmt: &Stmt, referenced: &mut HashSet<String>) {
+    match &stmt.kind {
+        StmtKind::Let(_, expr)
+        | StmtKind::Const(_, Some(expr))
+        | StmtKind::Return(expr)
+        | StmtKind::Expr(expr) => collect_references_expr(expr, referenced),
+        StmtKind::Assign(name, expr) => {
+            referenced.insert(name.clone());
+            collect_references_expr(expr, referenced);
+        }
+        StmtKind::If(cond, then_branch, else_branch) => {
+            collect_references_expr(cond, referenced);
+            collect_references_stmt(then_branch, referenced);
+            if let Some(else_branch) = else_branch {
+                collect_references_stmt(else_branch, referenced);
+            }
+        }
+        StmtKind::While(cond, body) | StmtKind::DoWhile(body, cond) => {
+            collect_references_expr(cond, referenced);
+            collect_references_stmt(body, referenced);
+        }
+        StmtKind::For(init, cond, incr, body) => {
+            if let Some(init) = init {
+                collect_references_stmt(init, referenced);
+            }
+            if let Some(cond) = cond {
+                collect_references_expr(cond, referenced);
+            }
+            if let Some(incr) = incr {
+                collect_references_stmt(incr, referenced);
+            }
+            collect_references_stmt(body, referenced);
+        }
+        StmtKind::Switch(disc, cases, default) => {
+            collect_references_expr(disc, referenced);
+            for (label, body) in cases {
+                collect_references_expr(label, referenced);
+                for stmt in body {
+                    collect_references_stmt(stmt, referenced);
+                }
+            }
+            for stmt in default.iter().flatten() {
+                collect_references_stmt(stmt, referenced);
+            }
+        }
+        StmtKind::Block(stmts) => {
+            for stmt in stmts {
+                collect_references_stmt(stmt, referenced);
+            }
+        }
+        StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+    }
+}
+
+fn collect_references_expr(expr: &Expr, referenced: &mut HashSet<String>) {
+    match expr {
+        Expr::Identifier(name) | Expr::Update(_, name) => {
+            referenced.insert(name.clone());
+        }
+        Expr::Assign(name, value) => {
+            referenced.insert(name.clone());
+            collect_references_expr(value, referenced);
+        }
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            collect_references_expr(left, referenced);
+            collect_references_expr(right, referenced);
+        }
+        Expr::Unary(_, operand) | Expr::Member(operand, _) => {
+            collect_references_expr(operand, referenced)
+        }
+        Expr::Call(_, args) => {
+            for arg in args {
+                collect_references_expr(arg, referenced);
+            }
+        }
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            collect_references_expr(cond, referenced);
+            collect_references_expr(then_expr, referenced);
+            collect_references_expr(else_expr, referenced);
+        }
+        Expr::ArrayLit(elements) => {
+            for element in elements {
+                collect_references_expr(element, referenced);
+            }
+        }
+        Expr::Index(array, index) => {
+            collect_references_expr(array, referenced);
+            collect_references_expr(index, referenced);
+        }
+        Expr::Number(_) | Expr::NumberF32(_) | Expr::StringLit(_) | Expr::Null => {}
+    }
+}
+
+/// A custom AST transform, run after the built-in optimizations by
+/// [`compile_with_passes`](crate::compile_with_passes).
+pub trait Pass {
+    /// Shown in the context of errors the pass returns.
+    fn name(&self) -> &str;
+    fn run(&mut self, program: &mut Program) -> Result<()>;
+}
+
+/// Runs `passes` in order, stopping at the first error.
+pub fn run_passes(program: &mut Program, passes: &mut [Box<dyn Pass>]) -> Result<()> {
+    for pass in passes {
+        pass.run(program)
+            .map_err(|e| e.with_context(format!("in pass '{}'", pass.name())))?;
+    }
+    Ok(())
+}
+
+struct Optimizer {
+    function_return_types: HashMap<String, Type>,
+    variable_types: HashMap<String, Type>,
+    // Consts initialized to a nonzero i32 literal; safe divisors for `x / x`
+    nonzero_consts: HashSet<String>,
+    // Literal values of variables that never change, substituted for their uses
+    constants: HashMap<String, Expr>,
+    // Names assigned, updated or declared more than once in the current body
+    unstable: HashSet<String>,
+    // -O2: substitute `constants` for their uses and drop unused bindings
+    full: bool,
+    arithmetic: ArithmeticMode,
+    line: usize, // Line of the statement being optimized, for overflow errors
+}
+
+impl Optimizer {
+    fn new(program: &Program, arithmetic: ArithmeticMode, full: bool) -> Self {
+        let function_return_types = program
+            .functions
+            .iter()
+            .map(|func| (func.name.clone(), func.return_type.unwrap_or(Type::I32)))
+            .collect();
+
+        Optimizer {
+            function_return_types,
+            variable_types: HashMap::new(),
+            nonzero_consts: HashSet::new(),
+            constants: HashMap::new(),
+            unstable: HashSet::new(),
+            full,
+            arithmetic,
+            line: 0,
+        }
+    }
+
+    fn enter_function(&mut self, func: &Function) {
+        self.enter_top_level(&func.body);
+        for param in &func.params {
+            self.unstable.insert(param.clone());
+        }
+        if let Some(param_types) = &func.param_types {
+            for (param, param_type) in func.params.iter().zip(param_types) {
+                self.variable_types.insert(param.clone(), *param_type);
+            }
+        }
+    }
+
+    fn enter_top_level(&mut self, body: &[Stmt]) {
+        self.variable_types.clear();
+        self.nonzero_consts.clear();
+        self.constants.clear();
+        self.unstable = unstable_names(body);
+    }
+
+    fn declare(&mut self, name: &str, init: &Expr, is_const: bool) {
+        let var_type = self.infer_expr_type_quick(init);
+        self.variable_types.insert(name.to_string(), var_type);
+        if is_const && matches!(init, Expr::Number(n) if *n != 0) {
+            self.nonzero_consts.insert(name.to_string());
+        } else {
+            self.nonzero_consts.remove(name);
+        }
+        // A `const`, or a `let` that is never reassigned, holds its literal for good
+        let literal = matches!(init, Expr::Number(_) | Expr::NumberF32(_));
+        if literal && self.full && !self.unstable.contains(name) {
+            self.constants.insert(name.to_string(), init.clone());
+        } else {
+            self.constants.remove(name);
+        }
+    }
+
+    fn infer_expr_type_quick(&self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Number(_) => Type::I32,
+            Expr::NumberF32(_) => Type::F32,
+            Expr::StringLit(_) => Type::Str,
+            Expr::Null => Type::Null,
+            Expr::ArrayLit(_) => Type::Array,
+            Expr::Index(..) | Expr::Member(..) => Type::I32,
+            Expr::Binary(left, op, right) => {
+                // Comparisons and bitwise operators return i32
+                if matches!(
+                    op,
+                    BinOp::Eq
+                        | BinOp::Ne
+                        | BinOp::Lt
+                        | BinOp::Gt
+                        | BinOp::Le
+                        | BinOp::Ge
+                        | BinOp::And
+                        | BinOp::Or
+                        | BinOp::Xor
+                        | BinOp::Shl
+                        | BinOp::Shr
+                ) {
+                    return Type::I32;
+                }
+                self.widen(left, right)
+            }
+            Expr::Unary(op, operand) => match op {
+                UnaryOp::Neg => self.infer_expr_type_quick(operand),
+                UnaryOp::Not | UnaryOp::BitNot => Type::I32,
+            },
+            Expr::Logical(left, _, right) | Expr::Ternary(_, left, right) => {
+                self.widen(left, right)
+            }
+            Expr::Identifier(name) | Expr::Update(_, name) | Expr::Assign(name, _) => {
+                self.variable_types.get(name).copied().unwrap_or(Type::I32)
+            }
+            Expr::Call(name, _) => self
+                .function_return_types
+                .get(name)
+                .copied()
+                .unwrap_or(Type::I32),
+        }
+    }
+
+    fn widen(&self, left: &Expr, right: &Expr) -> Type {
+        if self.infer_expr_type_quick(left) == Type::F32
+            || self.infer_expr_type_quick(right) == Type::F32
+        {
+            Type::F32
+        } else {
+            Type::I32
+        }
+    }
+
+    // One optimization pass over a function or top-level body
+    fn optimize_body(&mut self, body: Vec<Stmt>) -> Result<Vec<Stmt>> {
+        let mut body = self.optimize_stmts(body)?;
+        if self.full {
+            remove_dead_bindings(&mut body, self.arithmetic);
+        }
+        Ok(body)
+    }
+
+    fn optimize_stmts(&mut self, stmts: Vec<Stmt>) -> Result<Vec<Stmt>> {
+        let mut result = Vec::new();
+
+        for stmt in stmts {
+            let optimized = self.optimize_stmt(stmt)?;
+
+            // Check if this statement is a return
+            let is_return = matches!(optimized.kind, StmtKind::Return(_));
+
+            result.push(optimized);
+
+            // Stop processing after return
+            if is_return {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn optimize_stmt(&mut self, stmt: Stmt) -> Result<Stmt> {
+        self.line = stmt.line;
+        let kind = match stmt.kind {
+            StmtKind::Let(name, expr) => {
+                let expr = self.fold_expr(expr)?;
+                self.declare(&name, &expr, false);
+                StmtKind::Let(name, expr)
+            }
+            StmtKind::Const(name, Some(expr)) => {
+                let expr = self.fold_expr(expr)?;
+                self.declare(&name, &expr, true);
+                StmtKind::Const(name, Some(expr))
+            }
+            StmtKind::Const(name, None) => StmtKind::Const(name, None),
+            StmtKind::Assign(name, expr) => StmtKind::Assign(name, self.fold_expr(expr)?),
+            StmtKind::If(cond, then_branch, else_branch) => {
+                let cond = self.fold_expr(cond)?;
 
-            // Dead code: for with false condition
-            if let Some(cond_expr) = &cond {
-                let is_false = match cond_expr {
+                // Dead code: if (0) or if (0.0) -> remove
+                let is_false = match &cond {
                     Expr::Number(n) => *n == 0,
                     Expr::NumberF32(f) => *f == 0.0,
                     _ => false,
                 };
 
                 if is_false {
-                    // Condition is false - loop never executes
-                    // Just execute init if present, then return empty block
-                    return if let Some(init_stmt) = init {
-                        Stmt {
-                            kind: StmtKind::Block(vec![*init_stmt]),
-                            line: stmt.line,
-                        }
-                    } else {
-                        Stmt {
+                    // if (false) - use else branch or empty block
+                    return match else_branch {
+                        Some(eb) => self.optimize_stmt(*eb),
+                        None => Ok(Stmt {
                             kind: StmtKind::Block(vec![]),
                             line: stmt.line,
-                        }
+                        }),
+                    };
+                }
+
+                // if (non-zero constant) -> keep then only
+                let is_true = match &cond {
+                    Expr::Number(n) => *n != 0,
+                    Expr::NumberF32(f) => *f != 0.0,
+                    _ => false,
+                };
+
+                if is_true {
+                    return self.optimize_stmt(*then_branch);
+                }
+
+                let then_branch = Box::new(self.optimize_stmt(*then_branch)?);
+                let else_branch = else_branch
+                    .map(|eb| self.optimize_stmt(*eb).map(Box::new))
+                    .transpose()?;
+                StmtKind::If(cond, then_branch, else_branch)
+            }
+            StmtKind::While(cond, body) => {
+                let cond = self.fold_expr(cond)?;
+
+                // Dead code: while (0) or while (0.0) -> remove entirely
+                let is_false = match &cond {
+                    Expr::Number(n) => *n == 0,
+                    Expr::NumberF32(f) => *f == 0.0,
+                    _ => false,
+                };
+
+                if is_false {
+                    return Ok(Stmt {
+                        kind: StmtKind::Block(vec![]),
+                        line: stmt.line,
+                    });
+                }
+
+                let body = Box::new(self.optimize_stmt(*body)?);
+                StmtKind::While(cond, body)
+            }
+            StmtKind::DoWhile(body, cond) => {
+                let body = self.optimize_stmt(*body)?;
+                let cond = self.fold_expr(cond)?;
+
+                // do { ... } while (0) runs its body once; a break or continue
+                // in it still needs the loop
+                let is_false = match &cond {
+                    Expr::Number(n) => *n == 0,
+                    Expr::NumberF32(f) => *f == 0.0,
+                    _ => false,
+                };
+
+                if is_false && !body.exits_loop() {
+                    return Ok(body);
+                }
+
+                StmtKind::DoWhile(Box::new(body), cond)
+            }
+            StmtKind::For(init, cond, incr, body) => {
+                let init = init
+                    .map(|i| self.optimize_stmt(*i).map(Box::new))
+                    .transpose()?;
+                let cond = cond.map(|e| self.fold_expr(e)).transpose()?;
+                let incr = incr
+                    .map(|i| self.optimize_stmt(*i).map(Box::new))
+                    .transpose()?;
+
+                // Dead code: for with false condition
+                if let Some(cond_expr) = &cond {
+                    let is_false = match cond_expr {
+                        Expr::Number(n) => *n == 0,
+                        Expr::NumberF32(f) => *f == 0.0,
+                        _ => false,
                     };
+
+                    if is_false {
+                        // Condition is false - loop never executes
+                        // Just execute init if present, then return empty block
+                        let stmts = init.map(|init_stmt| vec![*init_stmt]).unwrap_or_default();
+                        return Ok(Stmt {
+                            kind: StmtKind::Block(stmts),
+                            line: stmt.line,
+                        });
+                    }
                 }
+
+                let body = Box::new(self.optimize_stmt(*body)?);
+                StmtKind::For(init, cond, incr, body)
+            }
+            StmtKind::Switch(disc, cases, default) => {
+                let disc = self.fold_expr(disc)?;
+                let cases = cases
+                    .into_iter()
+                    .map(|(label, body)| Ok((self.fold_expr(label)?, self.optimize_stmts(body)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                let default = default.map(|body| self.optimize_stmts(body)).transpose()?;
+                StmtKind::Switch(disc, cases, default)
             }
+            StmtKind::Block(stmts) => StmtKind::Block(self.optimize_stmts(stmts)?),
+            StmtKind::Return(expr) => StmtKind::Return(self.fold_expr(expr)?),
+            StmtKind::Break => StmtKind::Break,
+            StmtKind::Continue => StmtKind::Continue,
+            StmtKind::Expr(expr) => StmtKind::Expr(self.fold_expr(expr)?),
+        };
+
+        Ok(Stmt {
+            kind,
+            line: stmt.line,
+        })
+    }
 
-            let body = Box::new(optimize_stmt(*body));
-            StmtKind::For(init, cond, incr, body)
+    // `wide` is the exact i64 result of `a op b`. Wrapping mode truncates like
+    // i32.add / i32.sub / i32.mul; checked mode would trap at runtime, so it's an error.
+    fn fold_i32(&self, wide: i64, a: i32, op: &str, b: i32) -> Result<i32> {
+        match i32::try_from(wide) {
+            Ok(result) => Ok(result),
+            Err(_) if self.arithmetic == ArithmeticMode::Wrapping => Ok(wide as i32),
+            Err(_) => Err(CompilerError::semantic(
+                self.line,
+                format!(
+                    "Constant expression {} {} {} overflows i32 (checked arithmetic)",
+                    a, op, b
+                ),
+            )
+            .with_code(E_CONST_OVERFLOW)),
         }
-        StmtKind::Block(stmts) => StmtKind::Block(optimize_stmts(stmts)),
-        StmtKind::Return(expr) => StmtKind::Return(fold_expr(expr)),
-        StmtKind::Break => StmtKind::Break,
-        StmtKind::Continue => StmtKind::Continue,
-        StmtKind::Expr(expr) => StmtKind::Expr(fold_expr(expr)),
-    };
+    }
+
+    // `x + 0`, `0 + x`, `x - 0`, `x * 1`, `1 * x` and `x / 1` are x, and `x * 0` is 0,
+    // as long as the result keeps x's type. On f32 only the exact identities apply:
+    // `-0.0 + 0` is `0.0`, and `NaN * 0` is NaN.
+    fn simplify_identity(&self, left: &Expr, op: &BinOp, right: &Expr) -> Option<Expr> {
+        // Every operator below is arithmetic, so the result has the wider type
+        let result_type = self.widen(left, right);
+        let int = result_type == Type::I32;
+        let is = |expr: &Expr, value: i32| match expr {
+            Expr::Number(n) => *n == value,
+            // Bitwise, so `-0.0` is not 0
+            Expr::NumberF32(f) => f.to_bits() == (value as f32).to_bits(),
+            _ => false,
+        };
+        let keep =
+            |expr: &Expr| (self.infer_expr_type_quick(expr) == result_type).then(|| expr.clone());
+        // Dropping an operand is only safe if evaluating it has no effect
+        let pure = |expr: &Expr| !has_side_effects(expr, self.arithmetic);
 
-    Stmt {
-        kind,
-        line: stmt.line,
+        match op {
+            BinOp::Add if int && is(right, 0) => keep(left),
+            BinOp::Add if int && is(left, 0) => keep(right),
+            BinOp::Sub if is(right, 0) => keep(left),
+            BinOp::Mul if is(right, 1) => keep(left),
+            BinOp::Mul if is(left, 1) => keep(right),
+            BinOp::Mul if int && is(right, 0) && pure(left) => Some(Expr::Number(0)),
+            BinOp::Mul if int && is(left, 0) && pure(right) => Some(Expr::Number(0)),
+            BinOp::Div if is(right, 1) => keep(left),
+            _ => None,
+        }
     }
-}
 
-fn fold_expr(expr: Expr) -> Expr {
-    match expr {
-        Expr::Binary(left, op, right) => {
-            let left = fold_expr(*left);
-            let right = fold_expr(*right);
-
-            // Fold i32 constants
-            if let (Expr::Number(a), Expr::Number(b)) = (&left, &right) {
-                let result = match op {
-                    BinOp::Add => a + b,
-                    BinOp::Sub => a - b,
-                    BinOp::Mul => a * b,
-                    BinOp::Div => a / b,
-                    BinOp::Mod => a % b,
-                    BinOp::Eq => {
-                        if a == b {
-                            1
-                        } else {
-                            0
+    // `x * 2^k` is `x << k`. `x / 2^k` rounds toward zero, which `x >> k` alone
+    // does not for negative x, so a bias of `2^k - 1` is added to negative x first:
+    // `(x + ((x >> 31) & (2^k - 1))) >> k`. That reads x twice, so division is only
+    // rewritten for a variable. Checked mode keeps `i32.mul`, whose overflow traps.
+    fn reduce_strength(&self, left: &Expr, op: &BinOp, right: &Expr) -> Option<Expr> {
+        if self.arithmetic == ArithmeticMode::Checked || self.widen(left, right) != Type::I32 {
+            return None;
+        }
+        // k for a literal 2^k, 1 <= k <= 30
+        let shift = |expr: &Expr| match expr {
+            Expr::Number(n) if *n > 1 && (*n as u32).is_power_of_two() => {
+                Some(n.trailing_zeros() as i32)
+            }
+            _ => None,
+        };
+        let shl = |x: &Expr, k: i32| {
+            Expr::Binary(Box::new(x.clone()), BinOp::Shl, Box::new(Expr::Number(k)))
+        };
+        match op {
+            BinOp::Mul => match (shift(left), shift(right)) {
+                (_, Some(k)) => Some(shl(left, k)),
+                (Some(k), None) => Some(shl(right, k)),
+                (None, None) => None,
+            },
+            BinOp::Div if matches!(left, Expr::Identifier(_)) => {
+                let k = shift(right)?;
+                let sign = Expr::Binary(
+                    Box::new(left.clone()),
+                    BinOp::Shr,
+                    Box::new(Expr::Number(31)),
+                );
+                let bias = Expr::Binary(
+                    Box::new(sign),
+                    BinOp::And,
+                    Box::new(Expr::Number((1 << k) - 1)),
+                );
+                let biased = Expr::Binary(Box::new(left.clone()), BinOp::Add, Box::new(bias));
+                Some(Expr::Binary(
+                    Box::new(biased),
+                    BinOp::Shr,
+                    Box::new(Expr::Number(k)),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn fold_expr(&self, expr: Expr) -> Result<Expr> {
+        let folded = match expr {
+            Expr::Binary(left, op, right) => {
+                let left = self.fold_expr(*left)?;
+                let right = self.fold_expr(*right)?;
+
+                // Fold i32 constants
+                if let (Expr::Number(a), Expr::Number(b)) = (&left, &right) {
+                    let result = match op {
+                        BinOp::Add => self.fold_i32(*a as i64 + *b as i64, *a, "+", *b)?,
+                        BinOp::Sub => self.fold_i32(*a as i64 - *b as i64, *a, "-", *b)?,
+                        BinOp::Mul => self.fold_i32(*a as i64 * *b as i64, *a, "*", *b)?,
+                        // Division by zero (or i32::MIN / -1) traps at runtime; leave it to WASM
+                        BinOp::Div => match a.checked_div(*b) {
+                            Some(result) => result,
+                            None => return Ok(Expr::Binary(Box::new(left), op, Box::new(right))),
+                        },
+                        BinOp::Mod => match a.checked_rem(*b) {
+                            Some(result) => result,
+                            None => return Ok(Expr::Binary(Box::new(left), op, Box::new(right))),
+                        },
+                        BinOp::Eq => {
+                            if a == b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                    BinOp::Ne => {
-                        if a != b {
-                            1
-                        } else {
-                            0
+                        BinOp::Ne => {
+                            if a != b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                    BinOp::Lt => {
-                        if a < b {
-                            1
-                        } else {
-                            0
+                        BinOp::Lt => {
+                            if a < b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                    BinOp::Gt => {
-                        if a > b {
-                            1
-                        } else {
-                            0
+                        BinOp::Gt => {
+                            if a > b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                    BinOp::Le => {
-                        if a <= b {
-                            1
-                        } else {
-                            0
+                        BinOp::Le => {
+                            if a <= b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                    BinOp::Ge => {
-                        if a >= b {
-                            1
-                        } else {
-                            0
+                        BinOp::Ge => {
+                            if a >= b {
+                                1
+                            } else {
+                                0
+                            }
                         }
-                    }
-                };
-                return Expr::Number(result);
-            }
+                        BinOp::And => a & b,
+                        BinOp::Or => a | b,
+                        BinOp::Xor => a ^ b,
+                        // Like i32.shl / i32.shr_s, only the low 5 bits of the amount count
+                        BinOp::Shl => a.wrapping_shl(*b as u32),
+                        BinOp::Shr => a.wrapping_shr(*b as u32),
+                    };
+                    return Ok(Expr::Number(result));
+                }
 
-            // Fold f32 constants
-            if let (Expr::NumberF32(a), Expr::NumberF32(b)) = (&left, &right) {
-                // For comparisons, return i32 result
-                match op {
-                    BinOp::Eq => {
-                        return Expr::Number(if a == b { 1 } else { 0 });
-                    }
-                    BinOp::Ne => {
-                        return Expr::Number(if a != b { 1 } else { 0 });
-                    }
-                    BinOp::Lt => {
-                        return Expr::Number(if a < b { 1 } else { 0 });
-                    }
-                    BinOp::Gt => {
-                        return Expr::Number(if a > b { 1 } else { 0 });
-                    }
-                    BinOp::Le => {
-                        return Expr::Number(if a <= b { 1 } else { 0 });
-                    }
-                    BinOp::Ge => {
-                        return Expr::Number(if a >= b { 1 } else { 0 });
+                // Fold f32 constants
+                if let (Expr::NumberF32(a), Expr::NumberF32(b)) = (&left, &right) {
+                    // For comparisons, return i32 result
+                    match op {
+                        BinOp::Eq => {
+                            return Ok(Expr::Number(if a == b { 1 } else { 0 }));
+                        }
+                        BinOp::Ne => {
+                            return Ok(Expr::Number(if a != b { 1 } else { 0 }));
+                        }
+                        BinOp::Lt => {
+                            return Ok(Expr::Number(if a < b { 1 } else { 0 }));
+                        }
+                        BinOp::Gt => {
+                            return Ok(Expr::Number(if a > b { 1 } else { 0 }));
+                        }
+                        BinOp::Le => {
+                            return Ok(Expr::Number(if a <= b { 1 } else { 0 }));
+                        }
+                        BinOp::Ge => {
+                            return Ok(Expr::Number(if a >= b { 1 } else { 0 }));
+                        }
+                        _ => {}
                     }
-                    _ => {}
+
+                    // For arithmetic operations, return f32 result
+                    let result = match op {
+                        BinOp::Add => a + b,
+                        BinOp::Sub => a - b,
+                        BinOp::Mul => a * b,
+                        BinOp::Div => a / b,
+                        // The same `a - b * trunc(a / b)` that codegen emits
+                        BinOp::Mod => a - b * (a / b).trunc(),
+                        BinOp::And | BinOp::Or | BinOp::Xor | BinOp::Shl | BinOp::Shr => {
+                            // Bitwise on f32 - shouldn't happen (semantic analyzer blocks it)
+                            // But handle it here for safety
+                            return Ok(Expr::Binary(Box::new(left), op, Box::new(right)));
+                        }
+                        _ => unreachable!(), // Comparisons handled above
+                    };
+                    return Ok(Expr::NumberF32(result));
                 }
 
-                // For arithmetic operations, return f32 result
-                let result = match op {
-                    BinOp::Add => a + b,
-                    BinOp::Sub => a - b,
-                    BinOp::Mul => a * b,
-                    BinOp::Div => a / b,
-                    BinOp::Mod => {
-                        // Modulo on f32 - shouldn't happen (semantic analyzer blocks it)
-                        // But handle it here for safety
-                        return Expr::Binary(Box::new(left), op, Box::new(right));
+                // Same-identifier folds, i32 only: `x - x` is NaN for an infinite f32
+                if let (Expr::Identifier(a), Expr::Identifier(b)) = (&left, &right) {
+                    if a == b && self.infer_expr_type_quick(&left) == Type::I32 {
+                        match op {
+                            BinOp::Sub => return Ok(Expr::Number(0)),
+                            // `x / x` traps when x is 0, so only fold a known nonzero const
+                            BinOp::Div if self.nonzero_consts.contains(a) => {
+                                return Ok(Expr::Number(1))
+                            }
+                            _ => {}
+                        }
                     }
-                    _ => unreachable!(), // Comparisons handled above
-                };
-                return Expr::NumberF32(result);
+                }
+
+                if let Some(simplified) = self.simplify_identity(&left, &op, &right) {
+                    return Ok(simplified);
+                }
+                if let Some(reduced) = self.reduce_strength(&left, &op, &right) {
+                    return Ok(reduced);
+                }
+
+                Expr::Binary(Box::new(left), op, Box::new(right))
             }
+            Expr::Unary(op, operand) => {
+                let operand = self.fold_expr(*operand)?;
 
-            Expr::Binary(Box::new(left), op, Box::new(right))
-        }
-        Expr::Unary(op, operand) => {
-            let operand = fold_expr(*operand);
-
-            // Fold i32 unary
-            if let Expr::Number(n) = operand {
-                let result = match op {
-                    UnaryOp::Neg => -n,
-                    UnaryOp::Not => {
-                        if n == 0 {
-                            1
-                        } else {
-                            0
+                // Fold i32 unary
+                if let Expr::Number(n) = operand {
+                    let result = match op {
+                        // Lowered as `0 - n`
+                        UnaryOp::Neg => self.fold_i32(-(n as i64), 0, "-", n)?,
+                        UnaryOp::Not => {
+                            if n == 0 {
+                                1
+                            } else {
+                                0
+                            }
                         }
+                        UnaryOp::BitNot => !n,
+                    };
+                    return Ok(Expr::Number(result));
+                }
+
+                // Fold f32 unary
+                if let Expr::NumberF32(f) = operand {
+                    match op {
+                        UnaryOp::Neg => return Ok(Expr::NumberF32(-f)),
+                        UnaryOp::Not => {
+                            // ! on f32 returns i32 (0 or 1)
+                            return Ok(Expr::Number(if f == 0.0 { 1 } else { 0 }));
+                        }
+                        // Rejected for f32 by semantic analysis
+                        UnaryOp::BitNot => {}
                     }
+                }
+
+                Expr::Unary(op, Box::new(operand))
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .into_iter()
+                    .map(|e| self.fold_expr(e))
+                    .collect::<Result<_>>()?;
+                Expr::Call(name, args)
+            }
+            Expr::Logical(left, op, right) => {
+                let left = self.fold_expr(*left)?;
+                let right = self.fold_expr(*right)?;
+                // A constant left operand decides the result, and a right operand that
+                // is dropped would never have run. It stands in for the whole expression
+                // only if it already has the result type; a literal left is converted.
+                let truthy = match left {
+                    Expr::Number(n) => Some(n != 0),
+                    Expr::NumberF32(f) => Some(f != 0.0),
+                    _ => None,
+                };
+                let result_type = self.widen(&left, &right);
+                let keeps_left = match (truthy, &op) {
+                    (Some(truthy), LogicalOp::And) => Some(!truthy),
+                    (Some(truthy), LogicalOp::Or | LogicalOp::Nullish) => Some(truthy),
+                    _ => None,
                 };
-                return Expr::Number(result);
+                match keeps_left {
+                    Some(true) => match left {
+                        Expr::Number(n) if result_type == Type::F32 => Expr::NumberF32(n as f32),
+                        left => left,
+                    },
+                    Some(false) if self.infer_expr_type_quick(&right) == result_type => right,
+                    // An i32 falsy value is 0, so a right operand of 0 makes `x || 0` and
+                    // `x ?? 0` just x, and `x && 0` always 0. That drops x, which must
+                    // then be evaluated for nothing but its value.
+                    _ if matches!(right, Expr::Number(0)) && result_type == Type::I32 => match op {
+                        LogicalOp::Or | LogicalOp::Nullish => left,
+                        LogicalOp::And if !has_side_effects(&left, self.arithmetic) => right,
+                        LogicalOp::And => Expr::Logical(Box::new(left), op, Box::new(right)),
+                    },
+                    _ => Expr::Logical(Box::new(left), op, Box::new(right)),
+                }
             }
-
-            // Fold f32 unary
-            if let Expr::NumberF32(f) = operand {
-                match op {
-                    UnaryOp::Neg => return Expr::NumberF32(-f),
-                    UnaryOp::Not => {
-                        // ! on f32 returns i32 (0 or 1)
-                        return Expr::Number(if f == 0.0 { 1 } else { 0 });
+            Expr::Ternary(cond, then_expr, else_expr) => {
+                let cond = self.fold_expr(*cond)?;
+                let then_expr = self.fold_expr(*then_expr)?;
+                let else_expr = self.fold_expr(*else_expr)?;
+                // A constant condition picks its branch, unless the other one would widen the result
+                let same_type = self.infer_expr_type_quick(&then_expr)
+                    == self.infer_expr_type_quick(&else_expr);
+                match cond {
+                    Expr::Number(n) if same_type => {
+                        if n != 0 {
+                            then_expr
+                        } else {
+                            else_expr
+                        }
                     }
+                    cond => Expr::Ternary(Box::new(cond), Box::new(then_expr), Box::new(else_expr)),
                 }
             }
-
-            Expr::Unary(op, Box::new(operand))
-        }
-        Expr::Call(name, args) => {
-            let args = args.into_iter().map(fold_expr).collect();
-            Expr::Call(name, args)
-        }
-        Expr::Logical(left, op, right) => {
-            let left = fold_expr(*left);
-            let right = fold_expr(*right);
-            Expr::Logical(Box::new(left), op, Box::new(right))
-        }
-        Expr::NumberF32(_) => expr, 
-        _ => expr,
+            Expr::Assign(name, value) => Expr::Assign(name, Box::new(self.fold_expr(*value)?)),
+            Expr::ArrayLit(elements) => Expr::ArrayLit(
+                elements
+                    .into_iter()
+                    .map(|e| self.fold_expr(e))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Index(array, index) => Expr::Index(
+                Box::new(self.fold_expr(*array)?),
+                Box::new(self.fold_expr(*index)?),
+            ),
+            Expr::Member(object, property) => {
+                Expr::Member(Box::new(self.fold_expr(*object)?), property)
+            }
+            // Constant propagation; the enclosing expression then folds as usual
+            Expr::Identifier(name) => match self.constants.get(&name) {
+                Some(value) => value.clone(),
+                None => Expr::Identifier(name),
+            },
+            Expr::NumberF32(_) => expr,
+            _ => expr,
+        };
+        Ok(folded)
     }
 }
@@ -1,59 +1,105 @@
 use crate::ast::*;
-use crate::error::{CompilerError, Result};
+use crate::error::{CompilerError, Result, E_INVALID_NUMBER, E_UNEXPECTED_TOKEN, E_UNSUPPORTED};
 use crate::lexer::Token;
 
 pub struct Parser {
-    tokens: Vec<(Token, usize)>,
+    /// Each token with its line and column
+    tokens: Vec<(Token, usize, usize)>,
     pos: usize,
+    // Whether to skip past a syntax error and continue; see `parse_program_all`
+    recover: bool,
+    errors: Vec<CompilerError>,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<(Token, usize)>) -> Self {
-        Parser { tokens, pos: 0 }
+    pub fn new(tokens: Vec<(Token, usize, usize)>) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            recover: false,
+            errors: Vec::new(),
+        }
     }
 
     fn peek(&self) -> &Token {
         self.tokens
             .get(self.pos)
-            .map(|(t, _)| t)
+            .map(|(t, _, _)| t)
             .unwrap_or(&Token::Eof)
     }
 
     fn peek_line(&self) -> usize {
-        self.tokens.get(self.pos).map(|(_, l)| *l).unwrap_or(0)
+        self.tokens.get(self.pos).map(|(_, l, _)| *l).unwrap_or(0)
+    }
+
+    // The line and column of the token at `index`
+    fn position(&self, index: usize) -> (usize, usize) {
+        self.tokens
+            .get(index)
+            .map(|(_, l, c)| (*l, *c))
+            .unwrap_or((0, 0))
+    }
+
+    // An unexpected-token error pointing at the token at `index`
+    fn unexpected(&self, index: usize, message: String) -> CompilerError {
+        let (line, col) = self.position(index);
+        CompilerError::parser(line, message)
+            .with_col(col)
+            .with_code(E_UNEXPECTED_TOKEN)
     }
 
     fn advance(&mut self) -> Token {
         let tok = self
             .tokens
             .get(self.pos)
-            .map(|(t, _)| t.clone())
+            .map(|(t, _, _)| t.clone())
             .unwrap_or(Token::Eof);
         self.pos += 1;
         tok
     }
 
+    fn unsupported(&self, word: &str, index: usize) -> CompilerError {
+        let (line, col) = self.position(index);
+        CompilerError::parser(line, format!("'{}' is not supported", word))
+            .with_col(col)
+            .with_code(E_UNSUPPORTED)
+    }
+
     fn expect(&mut self, expected: Token) -> Result<()> {
-        let line = self.peek_line();
+        let index = self.pos;
         let tok = self.advance();
+        if let Token::Reserved(word) = &tok {
+            return Err(self.unsupported(word, index));
+        }
         if tok != expected {
-            return Err(CompilerError::parser(
-                line,
-                format!("Expected {:?}, got {:?}", expected, tok),
-            ));
+            return Err(self.unexpected(index, format!("Expected {:?}, got {:?}", expected, tok)));
         }
         Ok(())
     }
 
+    // A name: a reserved word reads as unsupported rather than as a stray token
+    fn expect_identifier(&mut self, what: &str) -> Result<String> {
+        let index = self.pos;
+        match self.advance() {
+            Token::Identifier(name) => Ok(name),
+            Token::Reserved(word) => Err(self.unsupported(&word, index)),
+            t => Err(self.unexpected(index, format!("Expected {}, got {}", what, t))),
+        }
+    }
+
     pub fn parse_program(&mut self) -> Result<Program> {
         let mut functions = Vec::new();
         let mut top_level = Vec::new();
 
         while *self.peek() != Token::Eof {
-            if *self.peek() == Token::Function {
-                functions.push(self.parse_function()?);
+            let start = self.pos;
+            let parsed = if *self.peek() == Token::Function {
+                self.parse_function().map(|func| functions.push(func))
             } else {
-                top_level.push(self.parse_statement()?);
+                self.parse_statements().map(|stmts| top_level.extend(stmts))
+            };
+            if let Err(e) = parsed {
+                self.recover_from(e, start)?;
             }
         }
 
@@ -63,32 +109,122 @@ impl Parser {
         })
     }
 
+    /// Like [`parse_program`](Self::parse_program), but after a syntax error skips
+    /// to the end of the statement and keeps parsing, so every error is reported.
+    pub fn parse_program_all(&mut self) -> std::result::Result<Program, Vec<CompilerError>> {
+        self.recover = true;
+        let parsed = self.parse_program();
+        let mut errors = std::mem::take(&mut self.errors);
+        match parsed {
+            Ok(program) if errors.is_empty() => Ok(program),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                errors.push(e);
+                Err(errors)
+            }
+        }
+    }
+
+    // Parses a statement onto `stmts`, recovering from a syntax error if enabled
+    fn parse_statement_into(&mut self, stmts: &mut Vec<Stmt>) -> Result<()> {
+        let start = self.pos;
+        match self.parse_statements() {
+            Ok(parsed) => stmts.extend(parsed),
+            Err(e) => self.recover_from(e, start)?,
+        }
+        Ok(())
+    }
+
+    // A statement in a list; `let a = 1, b = 2;` becomes one statement per name
+    fn parse_statements(&mut self) -> Result<Vec<Stmt>> {
+        if matches!(self.peek(), Token::Let | Token::Const) {
+            return self.parse_declarations();
+        }
+        Ok(vec![self.parse_statement()?])
+    }
+
+    // `let` or `const` followed by one or more comma-separated `name = expr`.
+    // A `let` without `= expr` starts as 0.
+    fn parse_declarations(&mut self) -> Result<Vec<Stmt>> {
+        let is_const = self.advance() == Token::Const;
+        let mut decls = Vec::new();
+        loop {
+            let line = self.peek_line();
+            let name = self.expect_identifier("identifier")?;
+            let expr = if *self.peek() == Token::Eq {
+                self.advance();
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+            // A const without a value is left for the analyzer to reject
+            let kind = if is_const {
+                StmtKind::Const(name, expr)
+            } else {
+                StmtKind::Let(name, expr.unwrap_or(Expr::Number(0)))
+            };
+            decls.push(Stmt { kind, line });
+
+            if *self.peek() != Token::Comma {
+                break;
+            }
+            self.advance();
+        }
+        self.expect(Token::Semicolon)?;
+        Ok(decls)
+    }
+
+    // Records the error and skips the rest of the statement that started at
+    // `start`, or returns the error when not recovering
+    fn recover_from(&mut self, error: CompilerError, start: usize) -> Result<()> {
+        if !self.recover {
+            return Err(error);
+        }
+        self.errors.push(error);
+        self.synchronize();
+        // A token no statement can start with, such as a stray `}`
+        if self.pos == start {
+            self.advance();
+        }
+        Ok(())
+    }
+
+    // Skips past the next `;` or balanced `{ ... }` at this nesting level, or up
+    // to the `}` that closes the enclosing block
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Token::Eof => return,
+                Token::RBrace if depth == 0 => return,
+                Token::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                Token::LBrace => depth += 1,
+                Token::RBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
     fn parse_function(&mut self) -> Result<Function> {
         let line = self.peek_line();
         self.expect(Token::Function)?;
-        let name = match self.advance() {
-            Token::Identifier(s) => s,
-            t => {
-                return Err(CompilerError::parser(
-                    line,
-                    format!("Expected function name, got {:?}", t),
-                ))
-            }
-        };
+        let name = self.expect_identifier("function name")?;
         self.expect(Token::LParen)?;
 
         let mut params = Vec::new();
         if *self.peek() != Token::RParen {
             loop {
-                match self.advance() {
-                    Token::Identifier(s) => params.push(s),
-                    t => {
-                        return Err(CompilerError::parser(
-                            self.peek_line(),
-                            format!("Expected parameter name, got {:?}", t),
-                        ))
-                    }
-                }
+                params.push(self.expect_identifier("parameter name")?);
                 if *self.peek() == Token::Comma {
                     self.advance();
                 } else {
@@ -102,12 +238,12 @@ impl Parser {
         let mut body = Vec::new();
         while *self.peek() != Token::RBrace {
             if *self.peek() == Token::Eof {
-                return Err(CompilerError::parser(
-                    line,
-                    "Expected RBrace, got Eof".to_string(),
-                ));
+                return Err(
+                    CompilerError::parser(line, "Expected RBrace, got Eof".to_string())
+                        .with_code(E_UNEXPECTED_TOKEN),
+                );
             }
-            body.push(self.parse_statement()?);
+            self.parse_statement_into(&mut body)?;
         }
         self.expect(Token::RBrace)?;
 
@@ -118,43 +254,21 @@ impl Parser {
             return_type: None,
             body,
             line,
+            aliases: Vec::new(),
         })
     }
 
     fn parse_statement(&mut self) -> Result<Stmt> {
         let line = self.peek_line();
         let kind = match self.peek() {
-            Token::Let => {
-                self.advance();
-                let name = match self.advance() {
-                    Token::Identifier(s) => s,
-                    t => {
-                        return Err(CompilerError::parser(
-                            line,
-                            format!("Expected identifier, got {:?}", t),
-                        ))
-                    }
-                };
-                self.expect(Token::Eq)?;
-                let expr = self.parse_expr()?;
-                self.expect(Token::Semicolon)?;
-                StmtKind::Let(name, expr)
-            }
-            Token::Const => {
-                self.advance();
-                let name = match self.advance() {
-                    Token::Identifier(s) => s,
-                    t => {
-                        return Err(CompilerError::parser(
-                            line,
-                            format!("Expected identifier, got {:?}", t),
-                        ))
-                    }
-                };
-                self.expect(Token::Eq)?;
-                let expr = self.parse_expr()?;
-                self.expect(Token::Semicolon)?;
-                StmtKind::Const(name, expr)
+            // Statement lists go through `parse_statements`; this is a lone body
+            // such as `if (c) let a = 1, b = 2;`, whose names stay inside it anyway
+            Token::Let | Token::Const => {
+                let mut decls = self.parse_declarations()?;
+                if decls.len() == 1 {
+                    return Ok(decls.remove(0));
+                }
+                StmtKind::Block(decls)
             }
             Token::If => {
                 self.advance();
@@ -178,6 +292,72 @@ impl Parser {
                 let body = Box::new(self.parse_statement()?);
                 StmtKind::While(cond, body)
             }
+            Token::Do => {
+                self.advance();
+                let body = Box::new(self.parse_statement()?);
+                self.expect(Token::While)?;
+                self.expect(Token::LParen)?;
+                let cond = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                // As in JS, the `;` after `do ... while (c)` is optional
+                if *self.peek() == Token::Semicolon {
+                    self.advance();
+                }
+                StmtKind::DoWhile(body, cond)
+            }
+            Token::Switch => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let disc = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                self.expect(Token::LBrace)?;
+
+                let mut cases = Vec::new();
+                let mut default = None;
+                while *self.peek() != Token::RBrace {
+                    let clause = self.pos;
+                    let label = match self.advance() {
+                        Token::Case => Some(self.parse_expr()?),
+                        Token::Default => None,
+                        t => {
+                            return Err(self.unexpected(
+                                clause,
+                                format!("Expected Case or Default, got {:?}", t),
+                            ))
+                        }
+                    };
+                    // The AST keeps `default` apart from the cases, so it must come
+                    // last. The clause itself parses fine, so keep going when recovering.
+                    if default.is_some() {
+                        let (line, col) = self.position(clause);
+                        let error = CompilerError::parser(
+                            line,
+                            "'default' must be the last clause of a switch".to_string(),
+                        )
+                        .with_col(col)
+                        .with_code(E_UNSUPPORTED);
+                        if !self.recover {
+                            return Err(error);
+                        }
+                        self.errors.push(error);
+                    }
+                    self.expect(Token::Colon)?;
+
+                    let mut body = Vec::new();
+                    while !matches!(
+                        self.peek(),
+                        Token::Case | Token::Default | Token::RBrace | Token::Eof
+                    ) {
+                        self.parse_statement_into(&mut body)?;
+                    }
+                    match label {
+                        Some(label) => cases.push((label, body)),
+                        None => default = Some(body),
+                    }
+                }
+                self.expect(Token::RBrace)?;
+                StmtKind::Switch(disc, cases, default)
+            }
             Token::For => {
                 self.advance();
                 self.expect(Token::LParen)?;
@@ -188,15 +368,7 @@ impl Parser {
                 } else {
                     let init_stmt = if *self.peek() == Token::Let {
                         self.advance();
-                        let name = match self.advance() {
-                            Token::Identifier(s) => s,
-                            t => {
-                                return Err(CompilerError::parser(
-                                    line,
-                                    format!("Expected identifier, got {:?}", t),
-                                ))
-                            }
-                        };
+                        let name = self.expect_identifier("identifier")?;
                         self.expect(Token::Eq)?;
                         let expr = self.parse_expr()?;
                         self.expect(Token::Semicolon)?;
@@ -206,20 +378,12 @@ impl Parser {
                         }
                     } else if *self.peek() == Token::Const {
                         self.advance();
-                        let name = match self.advance() {
-                            Token::Identifier(s) => s,
-                            t => {
-                                return Err(CompilerError::parser(
-                                    line,
-                                    format!("Expected identifier, got {:?}", t),
-                                ))
-                            }
-                        };
+                        let name = self.expect_identifier("identifier")?;
                         self.expect(Token::Eq)?;
                         let expr = self.parse_expr()?;
                         self.expect(Token::Semicolon)?;
                         Stmt {
-                            kind: StmtKind::Const(name, expr),
+                            kind: StmtKind::Const(name, Some(expr)),
                             line: self.peek_line(),
                         }
                     } else if let Token::Identifier(_) = self.peek() {
@@ -235,8 +399,8 @@ impl Parser {
                             line: self.peek_line(),
                         }
                     } else {
-                        return Err(CompilerError::parser(
-                            line,
+                        return Err(self.unexpected(
+                            self.pos,
                             format!("Unexpected token in for init: {:?}", self.peek()),
                         ));
                     };
@@ -260,16 +424,25 @@ impl Parser {
                             Token::Identifier(s) => s,
                             _ => unreachable!(),
                         };
-                        self.expect(Token::Eq)?;
-                        let expr = self.parse_expr()?;
-                        Some(Box::new(Stmt {
-                            kind: StmtKind::Assign(name, expr),
-                            line: self.peek_line(),
-                        }))
+                        if *self.peek() == Token::Eq {
+                            self.advance();
+                            let expr = self.parse_expr()?;
+                            Some(Box::new(Stmt {
+                                kind: StmtKind::Assign(name, expr),
+                                line: self.peek_line(),
+                            }))
+                        } else {
+                            self.pos -= 1;
+                            let expr = self.parse_expr()?;
+                            Some(Box::new(Stmt {
+                                kind: expr_stmt(expr),
+                                line: self.peek_line(),
+                            }))
+                        }
                     } else {
                         let expr = self.parse_expr()?;
                         Some(Box::new(Stmt {
-                            kind: StmtKind::Expr(expr),
+                            kind: expr_stmt(expr),
                             line: self.peek_line(),
                         }))
                     }
@@ -288,9 +461,10 @@ impl Parser {
                         return Err(CompilerError::parser(
                             line,
                             "Expected RBrace, got Eof".to_string(),
-                        ));
+                        )
+                        .with_code(E_UNEXPECTED_TOKEN));
                     }
-                    stmts.push(self.parse_statement()?);
+                    self.parse_statement_into(&mut stmts)?;
                 }
                 self.expect(Token::RBrace)?;
                 StmtKind::Block(stmts)
@@ -325,20 +499,63 @@ impl Parser {
                     self.pos -= 1;
                     let expr = self.parse_expr()?;
                     self.expect(Token::Semicolon)?;
-                    StmtKind::Expr(expr)
+                    expr_stmt(expr)
                 }
             }
             _ => {
                 let expr = self.parse_expr()?;
                 self.expect(Token::Semicolon)?;
-                StmtKind::Expr(expr)
+                expr_stmt(expr)
             }
         };
         Ok(Stmt { kind, line })
     }
 
     fn parse_expr(&mut self) -> Result<Expr> {
-        self.parse_or()
+        self.parse_assignment()
+    }
+
+    // Lowest precedence and right-associative: `a = b = 5` stores 5 in b, then in a
+    fn parse_assignment(&mut self) -> Result<Expr> {
+        let is_assignment = matches!(self.peek(), Token::Identifier(_))
+            && matches!(self.tokens.get(self.pos + 1), Some((Token::Eq, _, _)));
+        if !is_assignment {
+            return self.parse_ternary();
+        }
+        let name = match self.advance() {
+            Token::Identifier(s) => s,
+            _ => unreachable!(),
+        };
+        self.advance();
+        let value = self.parse_assignment()?;
+        Ok(Expr::Assign(name, Box::new(value)))
+    }
+
+    // Right-associative: `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+    fn parse_ternary(&mut self) -> Result<Expr> {
+        let cond = self.parse_nullish()?;
+        if *self.peek() != Token::Question {
+            return Ok(cond);
+        }
+        self.advance();
+        let then_expr = self.parse_ternary()?;
+        self.expect(Token::Colon)?;
+        let else_expr = self.parse_ternary()?;
+        Ok(Expr::Ternary(
+            Box::new(cond),
+            Box::new(then_expr),
+            Box::new(else_expr),
+        ))
+    }
+
+    fn parse_nullish(&mut self) -> Result<Expr> {
+        let mut left = self.parse_or()?;
+        while *self.peek() == Token::QuestionQuestion {
+            self.advance();
+            let right = self.parse_or()?;
+            left = Expr::Logical(Box::new(left), LogicalOp::Nullish, Box::new(right));
+        }
+        Ok(left)
     }
 
     fn parse_or(&mut self) -> Result<Expr> {
@@ -356,11 +573,11 @@ impl Parser {
     }
 
     fn parse_and(&mut self) -> Result<Expr> {
-        let mut left = self.parse_equality()?;
+        let mut left = self.parse_bit_or()?;
         loop {
             if *self.peek() == Token::AndAnd {
                 self.advance();
-                let right = self.parse_equality()?;
+                let right = self.parse_bit_or()?;
                 left = Expr::Logical(Box::new(left), LogicalOp::And, Box::new(right));
             } else {
                 break;
@@ -369,6 +586,37 @@ impl Parser {
         Ok(left)
     }
 
+    // Bitwise `|`, `^`, `&` bind looser than equality, as in JS: `a & 1 == 1` is `a & (1 == 1)`
+    fn parse_bit_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_bit_xor()?;
+        while *self.peek() == Token::Pipe {
+            self.advance();
+            let right = self.parse_bit_xor()?;
+            left = Expr::Binary(Box::new(left), BinOp::Or, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_bit_xor(&mut self) -> Result<Expr> {
+        let mut left = self.parse_bit_and()?;
+        while *self.peek() == Token::Caret {
+            self.advance();
+            let right = self.parse_bit_and()?;
+            left = Expr::Binary(Box::new(left), BinOp::Xor, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_equality()?;
+        while *self.peek() == Token::Amp {
+            self.advance();
+            let right = self.parse_equality()?;
+            left = Expr::Binary(Box::new(left), BinOp::And, Box::new(right));
+        }
+        Ok(left)
+    }
+
     fn parse_equality(&mut self) -> Result<Expr> {
         let mut left = self.parse_comparison()?;
         loop {
@@ -385,7 +633,7 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> Result<Expr> {
-        let mut left = self.parse_additive()?;
+        let mut left = self.parse_shift()?;
         loop {
             let op = match self.peek() {
                 Token::Lt => BinOp::Lt,
@@ -395,6 +643,21 @@ impl Parser {
                 _ => break,
             };
             self.advance();
+            let right = self.parse_shift()?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_shift(&mut self) -> Result<Expr> {
+        let mut left = self.parse_additive()?;
+        loop {
+            let op = match self.peek() {
+                Token::Shl => BinOp::Shl,
+                Token::Shr => BinOp::Shr,
+                _ => break,
+            };
+            self.advance();
             let right = self.parse_additive()?;
             left = Expr::Binary(Box::new(left), op, Box::new(right));
         }
@@ -434,29 +697,91 @@ impl Parser {
 
     fn parse_unary(&mut self) -> Result<Expr> {
         match self.peek() {
+            Token::PlusPlus | Token::MinusMinus => {
+                let op = if self.advance() == Token::PlusPlus {
+                    UpdateOp::PreInc
+                } else {
+                    UpdateOp::PreDec
+                };
+                Ok(Expr::Update(op, self.expect_identifier("identifier")?))
+            }
             Token::Minus => {
                 self.advance();
+                // `-2147483648` is the literal i32::MIN, not a negation that overflows
+                if *self.peek() == Token::Number(i32::MIN) {
+                    self.advance();
+                    return Ok(Expr::Number(i32::MIN));
+                }
                 Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.parse_unary()?)))
             }
             Token::Bang => {
                 self.advance();
                 Ok(Expr::Unary(UnaryOp::Not, Box::new(self.parse_unary()?)))
             }
-            _ => self.parse_primary(),
+            Token::Tilde => {
+                self.advance();
+                Ok(Expr::Unary(UnaryOp::BitNot, Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_postfix(),
+        }
+    }
+
+    // Indexing and member access bind tighter than any operator: `-a[0]` is
+    // `-(a[0])`, and `a[0].length` reads the length of `a[0]`
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            match self.peek() {
+                Token::LBracket => {
+                    self.advance();
+                    let index = self.parse_expr()?;
+                    self.expect(Token::RBracket)?;
+                    expr = Expr::Index(Box::new(expr), Box::new(index));
+                }
+                Token::Dot => {
+                    self.advance();
+                    let property = self.expect_identifier("property name")?;
+                    expr = Expr::Member(Box::new(expr), property);
+                }
+                _ => return Ok(expr),
+            }
         }
     }
 
     fn parse_primary(&mut self) -> Result<Expr> {
-        let line = self.peek_line();
         match self.peek().clone() {
+            // Only `-` can take 2^31 as its operand; `a - 2147483648` is out of range
+            Token::Number(i32::MIN) => Err(self
+                .unexpected(
+                    self.pos,
+                    format!("Integer literal too large: 2147483648 (max {})", i32::MAX),
+                )
+                .with_code(E_INVALID_NUMBER)),
             Token::Number(n) => {
                 self.advance();
                 Ok(Expr::Number(n))
             }
+            // Booleans are the i32 1 and 0 that comparisons produce
+            Token::True => {
+                self.advance();
+                Ok(Expr::Number(1))
+            }
+            Token::False => {
+                self.advance();
+                Ok(Expr::Number(0))
+            }
+            Token::Null => {
+                self.advance();
+                Ok(Expr::Null)
+            }
             Token::NumberF32(f) => {
                 self.advance();
                 Ok(Expr::NumberF32(f))
             }
+            Token::String(s) => {
+                self.advance();
+                Ok(Expr::StringLit(s))
+            }
             Token::Identifier(name) => {
                 self.advance();
                 if *self.peek() == Token::LParen {
@@ -474,6 +799,12 @@ impl Parser {
                     }
                     self.expect(Token::RParen)?;
                     Ok(Expr::Call(name, args))
+                } else if *self.peek() == Token::PlusPlus {
+                    self.advance();
+                    Ok(Expr::Update(UpdateOp::PostInc, name))
+                } else if *self.peek() == Token::MinusMinus {
+                    self.advance();
+                    Ok(Expr::Update(UpdateOp::PostDec, name))
                 } else {
                     Ok(Expr::Identifier(name))
                 }
@@ -484,10 +815,41 @@ impl Parser {
                 self.expect(Token::RParen)?;
                 Ok(expr)
             }
-            t => Err(CompilerError::parser(
-                line,
-                format!("Unexpected token in expression: {:?}", t),
-            )),
+            Token::LBracket => {
+                self.advance();
+                let mut elements = Vec::new();
+                if *self.peek() != Token::RBracket {
+                    loop {
+                        elements.push(self.parse_expr()?);
+                        if *self.peek() == Token::Comma {
+                            self.advance();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                self.expect(Token::RBracket)?;
+                Ok(Expr::ArrayLit(elements))
+            }
+            Token::Reserved(word) => Err(self.unsupported(&word, self.pos)),
+            t => Err(self.unexpected(self.pos, format!("Unexpected token in expression: {:?}", t))),
         }
     }
 }
+
+// A bare `i++;` or `--i;` discards its value, so it is just `i = i + 1`, and a
+// parenthesized `(x = 5);` is a plain assignment
+fn expr_stmt(expr: Expr) -> StmtKind {
+    match expr {
+        Expr::Assign(name, value) => StmtKind::Assign(name, *value),
+        Expr::Update(op, name) => StmtKind::Assign(
+            name.clone(),
+            Expr::Binary(
+                Box::new(Expr::Identifier(name)),
+                op.bin_op(),
+                Box::new(Expr::Number(1)),
+            ),
+        ),
+        expr => StmtKind::Expr(expr),
+    }
+}
@@ -1,6 +1,11 @@
 use crate::ast::*;
-use crate::error::{CompilerError, Result};
-use std::collections::HashMap;
+use crate::error::{
+    CompilerError, CompilerWarning, Result, WarningKind, E_ARGUMENT_COUNT, E_ARRAY_TYPE,
+    E_ASSIGN_TYPE, E_CONST_INIT, E_CONST_REASSIGN, E_DUPLICATE_FUNCTION, E_F32_BITWISE,
+    E_FUNCTION_AS_VALUE, E_MISSING_RETURN, E_NULL_TYPE, E_OUTSIDE_LOOP, E_PARAM_TYPE,
+    E_RETURN_TYPE, E_STRING_TYPE, E_UNDEFINED_FUNCTION, E_UNDEFINED_VARIABLE, E_VOID_VALUE,
+};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 struct VarInfo {
@@ -10,14 +15,37 @@ struct VarInfo {
 
 #[derive(Debug, Clone)]
 struct FunctionInfo {
+    line: usize,
+    param_count: usize,
     param_types: Option<Vec<Type>>, // None until first call
     return_type: Option<Type>,      // None until analyzed
+    returns_value: bool,            // false when the body has no `return`
 }
 
 pub struct SemanticAnalyzer {
     variables: Vec<HashMap<String, VarInfo>>, // Stack of scopes with type info
     functions: HashMap<String, FunctionInfo>, // Function signatures
     loop_depth: usize,
+    // `break` may also leave a `switch`; `continue` only a loop
+    switch_depth: usize,
+    warnings: Vec<CompilerWarning>,
+    allowed: Vec<WarningKind>,
+    enabled: Vec<WarningKind>,
+    max_params: usize,
+    // Errors gathered by `analyze_all`; `analyze` stops at the first instead
+    collect_errors: bool,
+    errors: Vec<CompilerError>,
+    // Whether `_start` returns a value, as it does for `--entry=start` and `export`
+    start_result: bool,
+    // Line and type of each top-level expression statement without side effects,
+    // gathered while top-level code is analyzed, for the unused-result lint
+    start_exprs: Option<Vec<(usize, Type)>>,
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SemanticAnalyzer {
@@ -26,28 +54,209 @@ impl SemanticAnalyzer {
             variables: vec![HashMap::new()],
             functions: HashMap::new(),
             loop_depth: 0,
+            switch_depth: 0,
+            warnings: Vec::new(),
+            allowed: Vec::new(),
+            enabled: Vec::new(),
+            max_params: 8,
+            collect_errors: false,
+            errors: Vec::new(),
+            start_result: true,
+            start_exprs: None,
+        }
+    }
+
+    /// Silences the given warning kinds.
+    pub fn allow(mut self, kinds: &[WarningKind]) -> Self {
+        self.allowed.extend_from_slice(kinds);
+        self
+    }
+
+    /// Turns on warning kinds that are off by default.
+    pub fn enable(mut self, kinds: &[WarningKind]) -> Self {
+        self.enabled.extend_from_slice(kinds);
+        self
+    }
+
+    /// Parameter count above which a function gets a `too-many-params` warning.
+    pub fn max_params(mut self, limit: usize) -> Self {
+        self.max_params = limit;
+        self
+    }
+
+    /// Whether `_start` returns the top-level code's result, which then does not
+    /// get an `unused-result` warning. Off for `--entry=wasi` and `--entry=none`,
+    /// where no value is returned.
+    pub fn start_result(mut self, returned: bool) -> Self {
+        self.start_result = returned;
+        self
+    }
+
+    pub fn warnings(&self) -> &[CompilerWarning] {
+        &self.warnings
+    }
+
+    fn warn(&mut self, kind: WarningKind, line: usize, message: String) {
+        // Function bodies are analyzed twice; report each warning once
+        let duplicate = self
+            .warnings
+            .iter()
+            .any(|w| w.line == line && w.message == message);
+        let on = kind.enabled_by_default() || self.enabled.contains(&kind);
+        if on && !self.allowed.contains(&kind) && !duplicate {
+            self.warnings
+                .push(CompilerWarning::new(line, message, kind));
         }
     }
 
+    // WASM shifts use only the low 5 bits of the amount, so `x << 33` shifts by 1
+    fn check_shift_amount(&mut self, amount: &Expr, line: usize) {
+        let amount = match amount {
+            Expr::Number(n) => *n,
+            Expr::Unary(UnaryOp::Neg, operand) => match operand.as_ref() {
+                Expr::Number(n) => n.wrapping_neg(),
+                _ => return,
+            },
+            _ => return,
+        };
+        if !(0..32).contains(&amount) {
+            self.warn(
+                WarningKind::ShiftAmount,
+                line,
+                format!(
+                    "Shift amount {} is outside 0..31; WASM masks it to {}",
+                    amount,
+                    amount & 31
+                ),
+            );
+        }
+    }
+
+    // Integer `/` truncates toward zero, so `7 / 2` is 3 rather than 3.5
+    fn check_int_division(&mut self, left: &Expr, right: &Expr, line: usize) {
+        if let (Expr::Number(a), Expr::Number(b)) = (left, right) {
+            if *b != 0 && a.wrapping_rem(*b) != 0 {
+                self.warn(
+                    WarningKind::IntDivision,
+                    line,
+                    format!(
+                        "Integer division truncates: {} / {} = {}",
+                        a,
+                        b,
+                        a.wrapping_div(*b)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Like [`analyze`](Self::analyze), but keeps going after an error and returns
+    /// all of them. Each function body and top-level statement reports at most one.
+    pub fn analyze_all(
+        &mut self,
+        program: &mut Program,
+    ) -> std::result::Result<(), Vec<CompilerError>> {
+        self.collect_errors = true;
+        let analyzed = self.analyze(program);
+        let mut errors = std::mem::take(&mut self.errors);
+        if let Err(e) = analyzed {
+            errors.push(e);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Stops analysis with the error, or records it when collecting
+    fn report(&mut self, error: CompilerError) -> Result<()> {
+        if !self.collect_errors {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+
     pub fn analyze(&mut self, program: &mut Program) -> Result<()> {
         // Register all functions first (without types yet)
         for func in &program.functions {
-            self.functions.insert(
+            // Checked here because function bodies are analyzed twice
+            if func.params.len() > self.max_params {
+                self.warn(
+                    WarningKind::TooManyParams,
+                    func.line,
+                    format!(
+                        "Function '{}' has {} parameters (more than {})",
+                        func.name,
+                        func.params.len(),
+                        self.max_params
+                    ),
+                );
+            }
+            let previous = self.functions.insert(
                 func.name.clone(),
                 FunctionInfo {
+                    line: func.line,
+                    param_count: func.params.len(),
                     param_types: None,
                     return_type: None,
+                    returns_value: contains_return(&func.body),
                 },
             );
+            if let Some(previous) = previous {
+                self.report(
+                    CompilerError::semantic(
+                        func.line,
+                        format!(
+                            "Function '{}' is already defined at line {}",
+                            func.name, previous.line
+                        ),
+                    )
+                    .with_code(E_DUPLICATE_FUNCTION),
+                )?;
+            }
         }
 
-        // First pass: Analyze each function with default i32 params
+        // First pass: Analyze each function with default i32 params. A function
+        // that only type-checks with its real argument types (e.g. a string param
+        // passed to print) is held back until we know whether it is called.
+        let mut provisional_errors = HashMap::new();
+        let mut failed = HashSet::new();
         for func in &program.functions {
-            self.analyze_function_with_params(func, &vec![Type::I32; func.params.len()])?;
+            let depth = (self.variables.len(), self.loop_depth, self.switch_depth);
+            if let Err(e) =
+                self.analyze_function_with_params(func, &vec![Type::I32; func.params.len()])
+            {
+                self.variables.truncate(depth.0);
+                self.loop_depth = depth.1;
+                self.switch_depth = depth.2;
+                if func.params.is_empty() {
+                    self.report(e)?;
+                    failed.insert(func.name.clone());
+                } else {
+                    provisional_errors.insert(func.name.clone(), e);
+                }
+            }
         }
 
-        // Analyze top-level code (this sets param types on first call)
-        self.analyze_stmts(&program.top_level)?;
+        // Analyze top-level code (this sets param types on first call). The last
+        // statement's value type is what `_start` returns.
+        self.start_exprs = Some(Vec::new());
+        let mut start_type = None;
+        for stmt in &program.top_level {
+            let depth = (self.variables.len(), self.loop_depth, self.switch_depth);
+            match self.analyze_stmt(stmt) {
+                Ok(value_type) => start_type = value_type,
+                Err(e) => {
+                    self.variables.truncate(depth.0);
+                    self.loop_depth = depth.1;
+                    self.switch_depth = depth.2;
+                    self.report(e)?;
+                }
+            }
+        }
+        let start_exprs = self.start_exprs.take().unwrap_or_default();
 
         // Second pass: Re-analyze functions whose param types were set
         for func in &program.functions {
@@ -56,22 +265,120 @@ impl SemanticAnalyzer {
                 func_info.param_types.clone() // Clone to drop the immutable borrow
             };
 
+            if failed.contains(&func.name) {
+                continue;
+            }
             if let Some(param_types) = param_types {
                 // Param types were set, re-analyze with correct types
-                self.analyze_function_with_params(func, &param_types)?;
+                let depth = (self.variables.len(), self.loop_depth, self.switch_depth);
+                if let Err(e) = self.analyze_function_with_params(func, &param_types) {
+                    self.variables.truncate(depth.0);
+                    self.loop_depth = depth.1;
+                    self.switch_depth = depth.2;
+                    self.report(e)?;
+                }
+            } else if let Some(e) = provisional_errors.remove(&func.name) {
+                self.report(e)?;
+            }
+        }
+        if !self.errors.is_empty() {
+            return Ok(());
+        }
+
+        // Lints that only need to see each statement once
+        for func in &program.functions {
+            self.check_unused_results(&func.body);
+        }
+        // `_start` keeps the value of every top-level expression statement of its
+        // result type, wherever it is, and returns the last one stored
+        let kept = self.start_result.then(|| start_type.unwrap_or(Type::I32));
+        for (line, expr_type) in start_exprs {
+            if Some(expr_type) != kept {
+                self.warn(
+                    WarningKind::UnusedResult,
+                    line,
+                    "Result of expression is unused".to_string(),
+                );
             }
         }
+        for func in &program.functions {
+            self.check_unused_variables(&func.params, func.line, &func.body);
+        }
+        self.check_unused_variables(&[], 0, &program.top_level);
 
-        // Write inferred types back to AST
+        // Write inferred types and resolved names back to AST; lints above
+        // report the names as written
         for func in &mut program.functions {
             let func_info = self.functions.get(&func.name).unwrap();
             func.param_types = func_info.param_types.clone();
             func.return_type = func_info.return_type;
+            resolve_names(&func.params, &mut func.body);
         }
+        resolve_names(&[], &mut program.top_level);
 
         Ok(())
     }
 
+    fn check_unused_results(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            match &stmt.kind {
+                // Calls may have side effects, so any expression containing one counts as used
+                StmtKind::Expr(expr) if !contains_call(expr) => self.warn(
+                    WarningKind::UnusedResult,
+                    stmt.line,
+                    "Result of expression is unused".to_string(),
+                ),
+                StmtKind::Block(inner) => self.check_unused_results(inner),
+                StmtKind::If(_, then_branch, else_branch) => {
+                    self.check_unused_results(std::slice::from_ref(then_branch));
+                    if let Some(eb) = else_branch {
+                        self.check_unused_results(std::slice::from_ref(eb));
+                    }
+                }
+                StmtKind::While(_, body)
+                | StmtKind::DoWhile(body, _)
+                | StmtKind::For(_, _, _, body) => {
+                    self.check_unused_results(std::slice::from_ref(body))
+                }
+                StmtKind::Switch(_, cases, default) => {
+                    for (_, body) in cases {
+                        self.check_unused_results(body);
+                    }
+                    if let Some(body) = default {
+                        self.check_unused_results(body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Walks the body with its own scope stack, since the analyzer's scopes are
+    // gone by the time a function has been analyzed
+    fn check_unused_variables(&mut self, params: &[String], line: usize, body: &[Stmt]) {
+        let mut tracker = ReadTracker::default();
+        tracker.enter_scope();
+        for param in params {
+            tracker.declare(param, line, true);
+        }
+        tracker.stmts(body);
+        tracker.exit_scope();
+
+        tracker.unused.sort_by_key(|var| var.line);
+        for var in tracker.unused {
+            let what = if var.is_param {
+                "parameter"
+            } else {
+                "variable"
+            };
+            self.warn(
+                WarningKind::UnusedVariable,
+                var.line,
+                format!("Unused {} '{}'", what, var.name),
+            );
+        }
+    }
+
     fn analyze_function_with_params(
         &mut self,
         func: &Function,
@@ -93,12 +400,20 @@ impl SemanticAnalyzer {
         // Analyze body statements FIRST so variables are declared
         self.analyze_stmts(&func.body)?;
 
-        // THEN infer return type by searching for Return statements
+        // THEN infer return type by searching for Return statements;
+        // a function without any stays void
         let return_type = self.infer_return_type_from_stmts(&func.body)?;
-
-        // Store return type (default to i32 if no return)
-        let return_type = return_type.unwrap_or(Type::I32);
-        self.functions.get_mut(&func.name).unwrap().return_type = Some(return_type);
+        if return_type.is_some() && !always_returns(&func.body) {
+            return Err(CompilerError::semantic(
+                func.line,
+                format!(
+                    "Function '{}' may complete without returning a value",
+                    func.name
+                ),
+            )
+            .with_code(E_MISSING_RETURN));
+        }
+        self.functions.get_mut(&func.name).unwrap().return_type = return_type;
 
         self.exit_scope();
         Ok(())
@@ -112,17 +427,28 @@ impl SemanticAnalyzer {
             let stmt_return_type = match &stmt.kind {
                 StmtKind::Return(expr) => Some(self.infer_expr_type(expr, stmt.line)?),
                 StmtKind::If(_, then_branch, else_branch) => {
-                    let then_type = self.infer_return_type_from_stmts(&[*then_branch.clone()])?;
+                    let then_type =
+                        self.infer_return_type_from_stmts(std::slice::from_ref(then_branch))?;
                     let else_type = if let Some(eb) = else_branch {
-                        self.infer_return_type_from_stmts(&[*eb.clone()])?
+                        self.infer_return_type_from_stmts(std::slice::from_ref(eb))?
                     } else {
                         None
                     };
                     then_type.or(else_type)
                 }
-                StmtKind::While(_, body) => self.infer_return_type_from_stmts(&[*body.clone()])?,
+                StmtKind::While(_, body) | StmtKind::DoWhile(body, _) => {
+                    self.infer_return_type_from_stmts(std::slice::from_ref(body))?
+                }
                 StmtKind::For(_, _, _, body) => {
-                    self.infer_return_type_from_stmts(&[*body.clone()])?
+                    self.infer_return_type_from_stmts(std::slice::from_ref(body))?
+                }
+                StmtKind::Switch(_, cases, default) => {
+                    let mut switch_type = None;
+                    for body in cases.iter().map(|(_, body)| body).chain(default) {
+                        let body_type = self.infer_return_type_from_stmts(body)?;
+                        switch_type = switch_type.or(body_type);
+                    }
+                    switch_type
                 }
                 StmtKind::Block(inner_stmts) => self.infer_return_type_from_stmts(inner_stmts)?,
                 _ => None,
@@ -137,7 +463,8 @@ impl SemanticAnalyzer {
                                 "Inconsistent return types: expected {:?}, got {:?}",
                                 existing_type, found_type
                             ),
-                        ));
+                        )
+                        .with_code(E_RETURN_TYPE));
                     }
                 } else {
                     return_type = Some(found_type);
@@ -179,14 +506,19 @@ impl SemanticAnalyzer {
         self.get_variable_info(name).map(|info| info.var_type)
     }
 
-    fn analyze_stmts(&mut self, stmts: &[Stmt]) -> Result<()> {
+    // Yields the value type of the last statement, as `analyze_stmt` does
+    fn analyze_stmts(&mut self, stmts: &[Stmt]) -> Result<Option<Type>> {
+        let mut value_type = None;
         for stmt in stmts {
-            self.analyze_stmt(stmt)?;
+            value_type = self.analyze_stmt(stmt)?;
         }
-        Ok(())
+        Ok(value_type)
     }
 
-    fn analyze_stmt(&mut self, stmt: &Stmt) -> Result<()> {
+    // Yields the type of the value the statement leaves for `_start` to return,
+    // as codegen picks it: that of an expression statement, or of the last one
+    // at the end of a block or of either `if` branch
+    fn analyze_stmt(&mut self, stmt: &Stmt) -> Result<Option<Type>> {
         match &stmt.kind {
             StmtKind::Let(name, expr) => {
                 let expr_type = self.infer_expr_type(expr, stmt.line)?;
@@ -199,6 +531,21 @@ impl SemanticAnalyzer {
                 );
             }
             StmtKind::Const(name, expr) => {
+                let Some(expr) = expr else {
+                    // Declared anyway, so later uses don't also read as undefined
+                    self.variables.last_mut().unwrap().insert(
+                        name.clone(),
+                        VarInfo {
+                            is_const: true,
+                            var_type: Type::I32,
+                        },
+                    );
+                    return Err(CompilerError::semantic(
+                        stmt.line,
+                        format!("Missing initializer in const declaration '{}'", name),
+                    )
+                    .with_code(E_CONST_INIT));
+                };
                 let expr_type = self.infer_expr_type(expr, stmt.line)?;
                 self.variables.last_mut().unwrap().insert(
                     name.clone(),
@@ -209,78 +556,79 @@ impl SemanticAnalyzer {
                 );
             }
             StmtKind::Assign(name, expr) => {
-                if !self.is_variable_defined(name) {
-                    return Err(CompilerError::semantic(
-                        stmt.line,
-                        format!("Undefined variable '{}'", name),
-                    ));
-                }
-                if self.is_variable_const(name) {
-                    return Err(CompilerError::semantic(
-                        stmt.line,
-                        format!("Cannot reassign const variable '{}'", name),
-                    ));
-                }
-
-                // Type checking on assignment
-                let var_type = self.get_variable_type(name).unwrap();
-                let expr_type = self.infer_expr_type(expr, stmt.line)?;
-                if var_type != expr_type {
-                    return Err(CompilerError::semantic(
-                        stmt.line,
-                        format!(
-                            "Type mismatch: cannot assign {:?} to {:?} variable '{}'",
-                            expr_type, var_type, name
-                        ),
-                    ));
-                }
+                self.check_assign(name, expr, stmt.line)?;
             }
             StmtKind::If(cond, then_branch, else_branch) => {
-                self.infer_expr_type(cond, stmt.line)?;
-                self.analyze_stmt(then_branch)?;
-                if let Some(eb) = else_branch {
-                    self.analyze_stmt(eb)?;
-                }
+                self.infer_number_type(cond, stmt.line)?;
+                let then_type = self.analyze_stmt(then_branch)?;
+                let else_type = match else_branch {
+                    Some(eb) => self.analyze_stmt(eb)?,
+                    None => None,
+                };
+                return Ok(then_type.or(else_type));
             }
             StmtKind::While(cond, body) => {
-                self.infer_expr_type(cond, stmt.line)?;
+                self.infer_number_type(cond, stmt.line)?;
                 self.loop_depth += 1;
                 self.analyze_stmt(body)?;
                 self.loop_depth -= 1;
             }
+            StmtKind::DoWhile(body, cond) => {
+                self.loop_depth += 1;
+                self.analyze_stmt(body)?;
+                self.loop_depth -= 1;
+                self.infer_number_type(cond, stmt.line)?;
+            }
             StmtKind::For(init, cond, incr, body) => {
                 //For loops need their own scope for the init variable
                 self.enter_scope();
 
                 if let Some(init_stmt) = init {
-                    self.analyze_stmt(init_stmt)?;
+                    self.analyze_for_clause(init_stmt)?;
                 }
                 if let Some(cond_expr) = cond {
-                    self.infer_expr_type(cond_expr, stmt.line)?;
+                    self.infer_number_type(cond_expr, stmt.line)?;
                 }
                 self.loop_depth += 1;
                 self.analyze_stmt(body)?;
                 if let Some(incr_stmt) = incr {
-                    self.analyze_stmt(incr_stmt)?;
+                    self.analyze_for_clause(incr_stmt)?;
                 }
                 self.loop_depth -= 1;
 
                 self.exit_scope();
             }
+            StmtKind::Switch(disc, cases, default) => {
+                self.infer_number_type(disc, stmt.line)?;
+                // The clauses share one scope, as in JS
+                self.enter_scope();
+                self.switch_depth += 1;
+                for (label, body) in cases {
+                    self.infer_number_type(label, stmt.line)?;
+                    self.analyze_stmts(body)?;
+                }
+                if let Some(body) = default {
+                    self.analyze_stmts(body)?;
+                }
+                self.switch_depth -= 1;
+                self.exit_scope();
+            }
             StmtKind::Block(stmts) => {
                 self.enter_scope();
-                self.analyze_stmts(stmts)?;
+                let value_type = self.analyze_stmts(stmts)?;
                 self.exit_scope();
+                return Ok(value_type);
             }
             StmtKind::Return(expr) => {
                 self.infer_expr_type(expr, stmt.line)?;
             }
             StmtKind::Break => {
-                if self.loop_depth == 0 {
+                if self.loop_depth == 0 && self.switch_depth == 0 {
                     return Err(CompilerError::semantic(
                         stmt.line,
-                        "Break statement outside of loop".to_string(),
-                    ));
+                        "Break statement outside of loop or switch".to_string(),
+                    )
+                    .with_code(E_OUTSIDE_LOOP));
                 }
             }
             StmtKind::Continue => {
@@ -288,20 +636,173 @@ impl SemanticAnalyzer {
                     return Err(CompilerError::semantic(
                         stmt.line,
                         "Continue statement outside of loop".to_string(),
-                    ));
+                    )
+                    .with_code(E_OUTSIDE_LOOP));
                 }
             }
+            // A call statement may name a function that returns nothing
+            StmtKind::Expr(Expr::Call(name, args)) => {
+                return self.check_call(name, args, stmt.line);
+            }
             StmtKind::Expr(expr) => {
-                self.infer_expr_type(expr, stmt.line)?;
+                let expr_type = self.infer_expr_type(expr, stmt.line)?;
+                if let Some(start_exprs) = &mut self.start_exprs {
+                    // Calls may have side effects, so any expression containing one counts as used
+                    if !contains_call(expr) {
+                        start_exprs.push((stmt.line, expr_type));
+                    }
+                }
+                return Ok(Some(expr_type));
             }
         }
+        Ok(None)
+    }
+
+    // A for loop's init or update: its value is never kept, so it is not a
+    // candidate for the unused-result lint either
+    fn analyze_for_clause(&mut self, stmt: &Stmt) -> Result<()> {
+        let start_exprs = self.start_exprs.take();
+        let analyzed = self.analyze_stmt(stmt);
+        self.start_exprs = start_exprs;
+        analyzed.map(|_| ())
+    }
+
+    // Operands and conditions must be numbers; strings and null only support
+    // storing and passing
+    fn infer_number_type(&mut self, expr: &Expr, line: usize) -> Result<Type> {
+        let expr_type = self.infer_expr_type(expr, line)?;
+        check_number(expr_type, line)?;
+        Ok(expr_type)
+    }
+
+    fn is_host_call(&self, name: &str) -> bool {
+        !self.functions.contains_key(name) && is_host_function(name)
+    }
+
+    // A host call statement such as `print(x)`; the overload is chosen by the argument's type
+    fn check_host_call(&mut self, name: &str, args: &[Expr], line: usize) -> Result<()> {
+        if args.len() != 1 {
+            return Err(CompilerError::semantic(
+                line,
+                format!("Function '{}' expects 1 argument, got {}", name, args.len()),
+            )
+            .with_code(E_ARGUMENT_COUNT));
+        }
+        let arg_type = self
+            .infer_expr_type(&args[0], line)
+            .map_err(|e| e.with_context(format!("in argument 1 of call to '{}'", name)))?;
+        if host_function(name, arg_type).is_none() {
+            return Err(CompilerError::semantic(
+                line,
+                format!("Function '{}' cannot take {:?}", name, arg_type),
+            )
+            .with_code(E_PARAM_TYPE));
+        }
         Ok(())
     }
 
+    // An assignment statement or expression; yields the variable's type
+    fn check_assign(&mut self, name: &str, value: &Expr, line: usize) -> Result<Type> {
+        if !self.is_variable_defined(name) {
+            return Err(
+                CompilerError::semantic(line, format!("Undefined variable '{}'", name))
+                    .with_code(E_UNDEFINED_VARIABLE),
+            );
+        }
+        if self.is_variable_const(name) {
+            return Err(CompilerError::semantic(
+                line,
+                format!("Cannot reassign const variable '{}'", name),
+            )
+            .with_code(E_CONST_REASSIGN));
+        }
+
+        // Type checking on assignment
+        let var_type = self.get_variable_type(name).unwrap();
+        let expr_type = self.infer_expr_type(value, line)?;
+        if var_type != expr_type {
+            return Err(CompilerError::semantic(
+                line,
+                format!(
+                    "Type mismatch: cannot assign {:?} to {:?} variable '{}'",
+                    expr_type, var_type, name
+                ),
+            )
+            .with_code(E_ASSIGN_TYPE));
+        }
+        Ok(var_type)
+    }
+
+    // Checks a call's arguments; yields the result type, or None for a function
+    // (or host import) that returns nothing
+    fn check_call(&mut self, name: &str, args: &[Expr], line: usize) -> Result<Option<Type>> {
+        if self.is_host_call(name) {
+            self.check_host_call(name, args, line)?;
+            return Ok(None);
+        }
+
+        // Infer argument types
+        let arg_types: Vec<Type> = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                self.infer_expr_type(arg, line).map_err(|e| {
+                    e.with_context(format!("in argument {} of call to '{}'", i + 1, name))
+                })
+            })
+            .collect::<Result<Vec<Type>>>()?;
+
+        let func_info = self.functions.get_mut(name).ok_or_else(|| {
+            CompilerError::semantic(line, format!("Undefined function '{}'", name))
+                .with_code(E_UNDEFINED_FUNCTION)
+        })?;
+
+        if func_info.param_count != arg_types.len() {
+            return Err(CompilerError::semantic(
+                line,
+                format!(
+                    "Function '{}' expects {} argument{}, got {}",
+                    name,
+                    func_info.param_count,
+                    if func_info.param_count == 1 { "" } else { "s" },
+                    arg_types.len()
+                ),
+            )
+            .with_code(E_ARGUMENT_COUNT));
+        }
+
+        // First-call wins: set parameter types
+        if let Some(expected_types) = &func_info.param_types {
+            // Validate subsequent calls match
+            for (i, (expected, actual)) in expected_types.iter().zip(arg_types.iter()).enumerate() {
+                if expected != actual {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!(
+                            "Function '{}' parameter {} type mismatch: expected {:?}, got {:?}",
+                            name, i, expected, actual
+                        ),
+                    )
+                    .with_code(E_PARAM_TYPE));
+                }
+            }
+        } else {
+            func_info.param_types = Some(arg_types.clone());
+        }
+
+        // Return the function's return type
+        if !func_info.returns_value {
+            return Ok(None);
+        }
+        Ok(Some(func_info.return_type.unwrap_or(Type::I32)))
+    }
+
     fn infer_expr_type(&mut self, expr: &Expr, line: usize) -> Result<Type> {
         match expr {
             Expr::Number(_) => Ok(Type::I32),
             Expr::NumberF32(_) => Ok(Type::F32),
+            Expr::StringLit(_) => Ok(Type::Str),
+            Expr::Null => Ok(Type::Null),
             Expr::Identifier(name) => {
                 if let Some(var_type) = self.get_variable_type(name) {
                     Ok(var_type)
@@ -310,26 +811,35 @@ impl SemanticAnalyzer {
                     Err(CompilerError::semantic(
                         line,
                         format!("Cannot use function '{}' as a value", name),
-                    ))
+                    )
+                    .with_code(E_FUNCTION_AS_VALUE))
                 } else {
-                    Err(CompilerError::semantic(
-                        line,
-                        format!("Undefined variable '{}'", name),
-                    ))
+                    Err(
+                        CompilerError::semantic(line, format!("Undefined variable '{}'", name))
+                            .with_code(E_UNDEFINED_VARIABLE),
+                    )
                 }
             }
             Expr::Binary(left, op, right) => {
-                let left_type = self.infer_expr_type(left, line)?;
-                let right_type = self.infer_expr_type(right, line)?;
+                let left_type = self.infer_number_type(left, line)?;
+                let right_type = self.infer_number_type(right, line)?;
 
-                // Check modulo restriction
-                if matches!(op, BinOp::Mod) {
+                if is_bitwise(op) {
                     if left_type == Type::F32 || right_type == Type::F32 {
                         return Err(CompilerError::semantic(
                             line,
-                            "Modulo operation not supported for f32 types".to_string(),
-                        ));
+                            "Bitwise operators are not supported for f32 types".to_string(),
+                        )
+                        .with_code(E_F32_BITWISE));
+                    }
+                    if matches!(op, BinOp::Shl | BinOp::Shr) {
+                        self.check_shift_amount(right, line);
                     }
+                    return Ok(Type::I32);
+                }
+
+                if matches!(op, BinOp::Div) {
+                    self.check_int_division(left, right, line);
                 }
 
                 // Comparison operations always return i32
@@ -348,70 +858,564 @@ impl SemanticAnalyzer {
                 }
             }
             Expr::Unary(op, operand) => {
-                let operand_type = self.infer_expr_type(operand, line)?;
+                let operand_type = self.infer_number_type(operand, line)?;
                 match op {
                     UnaryOp::Neg => Ok(operand_type), // -5 is i32, -3.14 is f32
                     UnaryOp::Not => Ok(Type::I32),    // ! always returns i32 (0 or 1)
+                    UnaryOp::BitNot if operand_type == Type::F32 => Err(CompilerError::semantic(
+                        line,
+                        "Bitwise operators are not supported for f32 types".to_string(),
+                    )
+                    .with_code(E_F32_BITWISE)),
+                    UnaryOp::BitNot => Ok(Type::I32),
                 }
             }
-            Expr::Call(name, args) => {
-                // Infer argument types
-                let arg_types: Vec<Type> = args
-                    .iter()
-                    .map(|arg| self.infer_expr_type(arg, line))
-                    .collect::<Result<Vec<Type>>>()?;
-
-                let func_info = self.functions.get_mut(name).ok_or_else(|| {
-                    CompilerError::semantic(line, format!("Undefined function '{}'", name))
-                })?;
+            Expr::Call(name, args) => self.check_call(name, args, line)?.ok_or_else(|| {
+                CompilerError::semantic(
+                    line,
+                    format!("Function '{}' does not return a value", name),
+                )
+                .with_code(E_VOID_VALUE)
+            }),
+            Expr::Logical(left, _, right) => {
+                let left_type = self.infer_number_type(left, line)?;
+                let right_type = self.infer_number_type(right, line)?;
 
-                // First-call wins: set parameter types
-                if func_info.param_types.is_none() {
-                    func_info.param_types = Some(arg_types.clone());
+                // Logical operators return the type of last evaluated value
+                // With auto-conversion: widen to f32 if either is f32
+                if left_type == Type::F32 || right_type == Type::F32 {
+                    Ok(Type::F32)
                 } else {
-                    // Validate subsequent calls match
-                    let expected_types = func_info.param_types.as_ref().unwrap();
-                    if expected_types.len() != arg_types.len() {
+                    Ok(Type::I32)
+                }
+            }
+            Expr::Ternary(cond, then_expr, else_expr) => {
+                self.infer_number_type(cond, line)?;
+                let then_type = self.infer_expr_type(then_expr, line)?;
+                let else_type = self.infer_expr_type(else_expr, line)?;
+
+                if then_type == Type::Null || else_type == Type::Null {
+                    if then_type != else_type {
                         return Err(CompilerError::semantic(
                             line,
                             format!(
-                                "Function '{}' expects {} arguments, got {}",
-                                name,
-                                expected_types.len(),
-                                arg_types.len()
+                                "Ternary branches must both be null, got {:?} and {:?}",
+                                then_type, else_type
                             ),
-                        ));
+                        )
+                        .with_code(E_NULL_TYPE));
                     }
-                    for (i, (expected, actual)) in
-                        expected_types.iter().zip(arg_types.iter()).enumerate()
-                    {
-                        if expected != actual {
-                            return Err(CompilerError::semantic(
-                                line,
-                                format!(
-                                    "Function '{}' parameter {} type mismatch: expected {:?}, got {:?}",
-                                    name, i, expected, actual
-                                ),
-                            ));
-                        }
+                    return Ok(Type::Null);
+                }
+                if then_type == Type::Array || else_type == Type::Array {
+                    if then_type != else_type {
+                        return Err(CompilerError::semantic(
+                            line,
+                            format!(
+                                "Ternary branches must both be arrays, got {:?} and {:?}",
+                                then_type, else_type
+                            ),
+                        )
+                        .with_code(E_ARRAY_TYPE));
                     }
+                    return Ok(Type::Array);
+                }
+                if (then_type == Type::Str) != (else_type == Type::Str) {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!(
+                            "Ternary branches must both be strings or both numbers, got {:?} and {:?}",
+                            then_type, else_type
+                        ),
+                    )
+                    .with_code(E_STRING_TYPE));
                 }
 
-                // Return the function's return type
-                Ok(func_info.return_type.unwrap_or(Type::I32))
-            }
-            Expr::Logical(left, _, right) => {
-                let left_type = self.infer_expr_type(left, line)?;
-                let right_type = self.infer_expr_type(right, line)?;
-
-                // Logical operators return the type of last evaluated value
-                // With auto-conversion: widen to f32 if either is f32
-                if left_type == Type::F32 || right_type == Type::F32 {
+                // Widen to f32 if either branch is f32, as for `&&` and `||`
+                if then_type == Type::Str {
+                    Ok(Type::Str)
+                } else if then_type == Type::F32 || else_type == Type::F32 {
                     Ok(Type::F32)
                 } else {
                     Ok(Type::I32)
                 }
             }
+            Expr::Update(_, name) => {
+                let Some(var_type) = self.get_variable_type(name) else {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!("Undefined variable '{}'", name),
+                    )
+                    .with_code(E_UNDEFINED_VARIABLE));
+                };
+                if self.is_variable_const(name) {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!("Cannot reassign const variable '{}'", name),
+                    )
+                    .with_code(E_CONST_REASSIGN));
+                }
+                check_number(var_type, line)?;
+                Ok(var_type)
+            }
+            Expr::Assign(name, value) => self.check_assign(name, value, line),
+            Expr::ArrayLit(elements) => {
+                for element in elements {
+                    let element_type = self.infer_expr_type(element, line)?;
+                    if element_type != Type::I32 {
+                        return Err(CompilerError::semantic(
+                            line,
+                            format!("Array elements must be i32, got {:?}", element_type),
+                        )
+                        .with_code(E_ARRAY_TYPE));
+                    }
+                }
+                Ok(Type::Array)
+            }
+            Expr::Index(array, index) => {
+                let array_type = self.infer_expr_type(array, line)?;
+                if array_type != Type::Array {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!("Cannot index a value of type {:?}", array_type),
+                    )
+                    .with_code(E_ARRAY_TYPE));
+                }
+                let index_type = self.infer_number_type(index, line)?;
+                if index_type != Type::I32 {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!("Array index must be i32, got {:?}", index_type),
+                    )
+                    .with_code(E_ARRAY_TYPE));
+                }
+                Ok(Type::I32)
+            }
+            Expr::Member(object, property) => {
+                let object_type = self.infer_expr_type(object, line)?;
+                if object_type != Type::Array {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!(
+                            "Cannot read property '{}' of a value of type {:?}",
+                            property, object_type
+                        ),
+                    )
+                    .with_code(E_ARRAY_TYPE));
+                }
+                if property != "length" {
+                    return Err(CompilerError::semantic(
+                        line,
+                        format!("Arrays have no property '{}'", property),
+                    )
+                    .with_code(E_ARRAY_TYPE));
+                }
+                Ok(Type::I32)
+            }
+        }
+    }
+}
+
+fn is_bitwise(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::And | BinOp::Or | BinOp::Xor | BinOp::Shl | BinOp::Shr
+    )
+}
+
+// Resolves every name to the declaration it refers to. A `let` or `const` that reuses
+// a name already declared in the function (shadowing, or a sibling block) is renamed
+// `x.1`, `x.2`..., along with its uses, so later stages can treat names as
+// function-wide slots. `.` never appears in a source identifier, so these can't
+// collide with user names.
+fn resolve_names(params: &[String], stmts: &mut [Stmt]) {
+    let mut renamer = ScopeRenamer {
+        scopes: vec![HashMap::new()],
+        declared: HashMap::new(),
+    };
+    for param in params {
+        renamer.declare(param);
+    }
+    renamer.stmts(stmts);
+}
+
+struct ScopeRenamer {
+    // Source name to local name, innermost scope last
+    scopes: Vec<HashMap<String, String>>,
+    // How often each source name has been declared so far in the function
+    declared: HashMap<String, usize>,
+}
+
+impl ScopeRenamer {
+    // The local name for a new declaration of `name` in the innermost scope
+    fn declare(&mut self, name: &str) -> String {
+        let count = self.declared.entry(name.to_string()).or_insert(0);
+        let local = if *count == 0 {
+            name.to_string()
+        } else {
+            format!("{}.{}", name, count)
+        };
+        *count += 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), local.clone());
+        }
+        local
+    }
+
+    fn resolve(&self, name: &mut String) {
+        if let Some(local) = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.as_str()))
+        {
+            *name = local.clone();
+        }
+    }
+
+    fn stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            // The initializer still sees the outer variable
+            StmtKind::Let(name, expr) | StmtKind::Const(name, Some(expr)) => {
+                self.expr(expr);
+                *name = self.declare(name);
+            }
+            StmtKind::Assign(name, expr) => {
+                self.expr(expr);
+                self.resolve(name);
+            }
+            StmtKind::If(cond, then_branch, else_branch) => {
+                self.expr(cond);
+                self.stmt(then_branch);
+                if let Some(eb) = else_branch {
+                    self.stmt(eb);
+                }
+            }
+            StmtKind::While(cond, body) => {
+                self.expr(cond);
+                self.stmt(body);
+            }
+            StmtKind::DoWhile(body, cond) => {
+                self.stmt(body);
+                self.expr(cond);
+            }
+            StmtKind::For(init, cond, incr, body) => {
+                self.scopes.push(HashMap::new());
+                if let Some(init) = init {
+                    self.stmt(init);
+                }
+                if let Some(cond) = cond {
+                    self.expr(cond);
+                }
+                self.stmt(body);
+                if let Some(incr) = incr {
+                    self.stmt(incr);
+                }
+                self.scopes.pop();
+            }
+            StmtKind::Switch(disc, cases, default) => {
+                self.expr(disc);
+                self.scopes.push(HashMap::new());
+                for (label, body) in cases {
+                    self.expr(label);
+                    self.stmts(body);
+                }
+                if let Some(body) = default {
+                    self.stmts(body);
+                }
+                self.scopes.pop();
+            }
+            StmtKind::Block(inner) => {
+                self.scopes.push(HashMap::new());
+                self.stmts(inner);
+                self.scopes.pop();
+            }
+            StmtKind::Return(expr) | StmtKind::Expr(expr) => self.expr(expr),
+            StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Identifier(name) | Expr::Update(_, name) => self.resolve(name),
+            Expr::Assign(name, value) => {
+                self.expr(value);
+                self.resolve(name);
+            }
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Unary(_, operand) | Expr::Member(operand, _) => self.expr(operand),
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            Expr::Ternary(cond, then_expr, else_expr) => {
+                self.expr(cond);
+                self.expr(then_expr);
+                self.expr(else_expr);
+            }
+            Expr::ArrayLit(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            Expr::Index(array, index) => {
+                self.expr(array);
+                self.expr(index);
+            }
+            Expr::Number(_) | Expr::NumberF32(_) | Expr::StringLit(_) | Expr::Null => {}
+        }
+    }
+}
+
+struct DeclaredVar {
+    name: String,
+    line: usize,
+    is_param: bool,
+    read: bool,
+}
+
+// Variables declared in each enclosing scope, innermost last. Assignments are
+// not reads; names starting with `_` are never reported.
+#[derive(Default)]
+struct ReadTracker {
+    scopes: Vec<Vec<DeclaredVar>>,
+    unused: Vec<DeclaredVar>,
+}
+
+impl ReadTracker {
+    fn enter_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn exit_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        self.unused.extend(
+            scope
+                .into_iter()
+                .filter(|var| !var.read && !var.name.starts_with('_')),
+        );
+    }
+
+    fn declare(&mut self, name: &str, line: usize, is_param: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(DeclaredVar {
+                name: name.to_string(),
+                line,
+                is_param,
+                read: false,
+            });
+        }
+    }
+
+    fn read(&mut self, name: &str) {
+        let var = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|var| var.name == name));
+        if let Some(var) = var {
+            var.read = true;
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Let(name, expr) | StmtKind::Const(name, Some(expr)) => {
+                self.expr(expr);
+                self.declare(name, stmt.line, false);
+            }
+            StmtKind::Assign(_, expr) | StmtKind::Return(expr) | StmtKind::Expr(expr) => {
+                self.expr(expr)
+            }
+            StmtKind::If(cond, then_branch, else_branch) => {
+                self.expr(cond);
+                self.stmt(then_branch);
+                if let Some(eb) = else_branch {
+                    self.stmt(eb);
+                }
+            }
+            StmtKind::While(cond, body) => {
+                self.expr(cond);
+                self.stmt(body);
+            }
+            StmtKind::DoWhile(body, cond) => {
+                self.stmt(body);
+                self.expr(cond);
+            }
+            StmtKind::For(init, cond, update, body) => {
+                self.enter_scope();
+                if let Some(init) = init {
+                    self.stmt(init);
+                }
+                if let Some(cond) = cond {
+                    self.expr(cond);
+                }
+                self.stmt(body);
+                if let Some(update) = update {
+                    self.stmt(update);
+                }
+                self.exit_scope();
+            }
+            StmtKind::Switch(disc, cases, default) => {
+                self.expr(disc);
+                self.enter_scope();
+                for (label, body) in cases {
+                    self.expr(label);
+                    self.stmts(body);
+                }
+                if let Some(body) = default {
+                    self.stmts(body);
+                }
+                self.exit_scope();
+            }
+            StmtKind::Block(inner) => {
+                self.enter_scope();
+                self.stmts(inner);
+                self.exit_scope();
+            }
+            StmtKind::Const(_, None) | StmtKind::Break | StmtKind::Continue => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Identifier(name) | Expr::Update(_, name) => self.read(name),
+            // Storing is not reading, as for an assignment statement
+            Expr::Assign(_, value) => self.expr(value),
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Unary(_, operand) | Expr::Member(operand, _) => self.expr(operand),
+            Expr::Call(_, args) => {
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            Expr::Ternary(cond, then_expr, else_expr) => {
+                self.expr(cond);
+                self.expr(then_expr);
+                self.expr(else_expr);
+            }
+            Expr::ArrayLit(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            Expr::Index(array, index) => {
+                self.expr(array);
+                self.expr(index);
+            }
+            Expr::Number(_) | Expr::NumberF32(_) | Expr::StringLit(_) | Expr::Null => {}
+        }
+    }
+}
+
+// Whether a `return` appears anywhere in the body
+fn contains_return(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Return(_) => true,
+        StmtKind::If(_, then_branch, else_branch) => {
+            contains_return(std::slice::from_ref(then_branch))
+                || else_branch
+                    .as_ref()
+                    .is_some_and(|eb| contains_return(std::slice::from_ref(eb)))
+        }
+        StmtKind::While(_, body) | StmtKind::DoWhile(body, _) | StmtKind::For(_, _, _, body) => {
+            contains_return(std::slice::from_ref(body))
+        }
+        StmtKind::Switch(_, cases, default) => cases
+            .iter()
+            .map(|(_, body)| body)
+            .chain(default)
+            .any(|body| contains_return(body)),
+        StmtKind::Block(inner) => contains_return(inner),
+        _ => false,
+    })
+}
+
+// Whether every path through the statements reaches a `return`. An `if` does
+// only if both branches do; a `while` or `for` never does, since its body may not
+// run. A `do`/`while` body always runs, so it counts unless it can leave early.
+// A `switch` counts if it has a `default`, nothing breaks out of it, and the
+// `default` returns, since every case falls through to it.
+fn always_returns(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Return(_) => true,
+        StmtKind::If(_, then_branch, Some(else_branch)) => {
+            always_returns(std::slice::from_ref(then_branch))
+                && always_returns(std::slice::from_ref(else_branch))
+        }
+        StmtKind::DoWhile(body, _) => {
+            always_returns(std::slice::from_ref(body)) && !body.exits_loop()
+        }
+        StmtKind::Switch(_, cases, Some(default)) => {
+            always_returns(default)
+                && !cases
+                    .iter()
+                    .flat_map(|(_, body)| body)
+                    .chain(default)
+                    .any(Stmt::exits_loop)
+        }
+        StmtKind::Block(inner) => always_returns(inner),
+        _ => false,
+    })
+}
+
+fn check_number(t: Type, line: usize) -> Result<()> {
+    match t {
+        Type::I32 | Type::F32 => Ok(()),
+        Type::Str => Err(CompilerError::semantic(
+            line,
+            "Strings cannot be used in arithmetic, comparisons or conditions".to_string(),
+        )
+        .with_code(E_STRING_TYPE)),
+        Type::Null => Err(CompilerError::semantic(
+            line,
+            "null cannot be used in arithmetic, comparisons or conditions".to_string(),
+        )
+        .with_code(E_NULL_TYPE)),
+        Type::Array => Err(CompilerError::semantic(
+            line,
+            "Arrays cannot be used in arithmetic, comparisons or conditions".to_string(),
+        )
+        .with_code(E_ARRAY_TYPE)),
+    }
+}
+
+fn contains_call(expr: &Expr) -> bool {
+    match expr {
+        // Updates and assignments have a side effect just like a call
+        Expr::Call(..) | Expr::Update(..) | Expr::Assign(..) => true,
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            contains_call(left) || contains_call(right)
+        }
+        Expr::Unary(_, operand) | Expr::Member(operand, _) => contains_call(operand),
+        Expr::Ternary(cond, then_expr, else_expr) => {
+            contains_call(cond) || contains_call(then_expr) || contains_call(else_expr)
         }
+        Expr::ArrayLit(elements) => elements.iter().any(contains_call),
+        Expr::Index(array, index) => contains_call(array) || contains_call(index),
+        Expr::Number(_)
+        | Expr::NumberF32(_)
+        | Expr::StringLit(_)
+        | Expr::Null
+        | Expr::Identifier(_) => false,
     }
 }
@@ -0,0 +1,23 @@
+// Every path returns: both if/else branches, and a final return after the loop
+function classify(x) {
+  if (x < 0) {
+    return -1;
+  } else if (x == 0) {
+    return 0;
+  } else {
+    return 1;
+  }
+}
+
+function firstMultiple(n, step) {
+  let m = step;
+  while (m <= 1000) {
+    if (m % n == 0) {
+      return m;
+    }
+    m = m + step;
+  }
+  return 0;
+}
+
+classify(-5) + classify(0) + classify(9) + firstMultiple(12, 5);
@@ -0,0 +1,7 @@
+// Reading past the end of an array traps instead of reading whatever follows it
+function at(i) {
+  let a = [10, 20, 30];
+  return a[i];
+}
+
+at(3);
@@ -0,0 +1,16 @@
+// a.length reads the length word stored in front of the elements
+function count() {
+  let a = [1, 2, 3, 4];
+  return a.length;
+}
+
+function sum(xs) {
+  let total = 0;
+  for (let i = 0; i < xs.length; i++) {
+    total = total + xs[i];
+  }
+  return total;
+}
+
+// 4 + (5 + 6 + 7) * 10 + 0 * 1000
+count() + sum([5, 6, 7]) * 10 + [].length * 1000;
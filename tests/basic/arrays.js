@@ -0,0 +1,19 @@
+// Arrays of i32 live in linear memory: a length word, then the elements
+function second() {
+  let a = [10, 20, 30];
+  return a[1];
+}
+
+function sum(xs, n) {
+  let total = 0;
+  for (let i = 0; i < n; i++) {
+    total = total + xs[i];
+  }
+  return total;
+}
+
+let values = [1, [2][0], 3 * 4];
+let empty = [];
+
+// 20 + (1 + 2 + 12) * 100
+second() + sum(values, 3) * 100;
@@ -0,0 +1,14 @@
+// Assignment is an expression whose value is the one stored
+let x = 0;
+let y = (x = 5);
+
+// Stores into k and tests it in one condition: adds 4 + 3 + 2 + 1
+let n = 5;
+let total = 0;
+let k = 0;
+while ((k = n - 1) > 0) {
+  total = total + k;
+  n = k;
+}
+
+y * 100 + total;
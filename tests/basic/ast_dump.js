@@ -0,0 +1,4 @@
+// AST dump (--emit=ast): an indented tree of the checked, optimized program
+function f() { return 1 + 2; }
+
+f();
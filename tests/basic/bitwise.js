@@ -0,0 +1,15 @@
+// Test bitwise operators: & | ^ ~ << >>
+function bitwise(a, b, s) {
+  let r = a & b;           // 5 & 3 = 1
+  r = r + (1 << s);        // 1 << 4 = 16
+  r = r + ~(b - 3);        // ~0 = -1
+  r = r * 100 + (a ^ 6);   // 5 ^ 6 = 3
+  r = r + (-64 >> s);      // arithmetic shift keeps the sign: -4
+  r = r + (a | 8) * 10000; // 5 | 8 = 13
+  return r;
+}
+
+// Folded at compile time; shifts bind tighter than &, and & tighter than |
+const k = 5 & 3 | 1 << 4;
+
+bitwise(5, 3, 4) + k * 1000000;
@@ -0,0 +1,6 @@
+// _start returns the value of the last expression statement run at top level,
+// even one inside a block; 42 replaces 41
+{
+  41;
+}
+42;
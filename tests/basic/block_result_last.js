@@ -0,0 +1,4 @@
+// A block that ends the program gives _start its last expression's value
+{
+  7;
+}
@@ -0,0 +1,24 @@
+// Sibling blocks each declare x, and an inner x shadows the outer one;
+// each gets its own local, so the values stay independent
+function siblings() {
+  let r = 0;
+  {
+    let x = 30;
+    r = r + x;
+  }
+  {
+    let x = 2.5;
+    x = x * 2.0;
+    if (x > 4.0) {
+      r = r + 1;
+    }
+  }
+  return r;
+}
+
+let x = 100;
+{
+  let x = 7;
+  x = x + 1;
+}
+siblings() + x;
@@ -0,0 +1,17 @@
+// true and false are the i32 1 and 0, so they mix with comparisons and logic
+function isPositive(n) {
+  if (n > 0) return true;
+  return false;
+}
+
+function alwaysTrue() {
+  return true;
+}
+
+let flag = true && isPositive(5);
+if (false) {
+  flag = 777;
+}
+
+// 1 + 0 + 100 + 1000
+flag + isPositive(-1) * 10 + alwaysTrue() * 100 + (false || true) * 1000;
@@ -0,0 +1,7 @@
+// --entry decides what the top-level code becomes: _start (default), a WASI
+// (start) function, an exported main, or nothing
+function double(n) {
+  return n * 2;
+}
+
+double(21);
@@ -0,0 +1,10 @@
+// Postfix ++/-- yields the old value, prefix the new one
+function counters() {
+  let x = 5;
+  let y = x++;
+  let z = ++x;
+  let w = x--;
+  return y * 100 + z * 10 + (w - x);
+}
+
+counters();
@@ -0,0 +1,11 @@
+// -2147483648 is the literal i32::MIN, not a negation that overflows
+function smallest() {
+  let min = -2147483648;
+  let hex = -0x80000000;
+  if (min == hex) {
+    return min;
+  }
+  return 0;
+}
+
+smallest();
@@ -0,0 +1,13 @@
+// let without an initializer starts as 0
+function later() {
+  let x;
+  x = 5;
+  return x;
+}
+
+function untouched() {
+  let total, step = 4;
+  return total + step;
+}
+
+later() * 10 + untouched();
@@ -0,0 +1,11 @@
+// --list-exports prints each exported function's signature
+function add(a, b) {
+    return a + b;
+}
+
+function scale(x) {
+    return x * 2.5;
+}
+
+add(1, 2);
+scale(4.0);
@@ -0,0 +1,16 @@
+// Locals are declared in source order, compiler temporaries last
+function local_order(n) {
+    let total = 0;
+    if (n > 0) {
+        let bonus = n * 2;
+        total = total + bonus;
+    }
+    for (let i = 0; i < n; i = i + 1) {
+        let step = i;
+        total = total + step;
+    }
+    let last = total;
+    return last;
+}
+
+local_order(4);
@@ -0,0 +1,8 @@
+// Several declarators in one let or const; each sees the ones before it
+function pair(x) {
+  let a = x, b = a + 1;
+  const c = b * 2, d = c + a;
+  return a * 1000 + b * 100 + c * 10 + d;
+}
+
+pair(3);
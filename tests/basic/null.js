@@ -0,0 +1,9 @@
+// null can be stored, copied and returned; it lowers to the i32 sentinel -2147483648
+function nothing() {
+  let x = null;
+  return x;
+}
+
+let empty = nothing();
+let copy = empty;
+copy;
@@ -0,0 +1,9 @@
+// Nullish coalescing: `a ?? b` yields a unless a is 0, else b
+function nullish(zero, three) {
+    let a = zero ?? 5;
+    let b = three ?? 5;
+    let c = zero ?? zero ?? 7;
+    return a * 100 + b * 10 + c;
+}
+
+nullish(0, 3);
@@ -0,0 +1,6 @@
+// Opcode histogram (--opcode-stats): three i32.add instructions
+function sum4(a, b, c, d) {
+  return a + b + c + d;
+}
+
+sum4(1, 2, 3, 4);
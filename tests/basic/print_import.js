@@ -0,0 +1,2 @@
+// print is a host import; it returns nothing, so the call is not dropped
+print(42);
@@ -0,0 +1,14 @@
+// 0x, 0b and 0o read hex, binary and octal integers; a bare 0 is still decimal
+function matches() {
+  let hex = 0xFF == 255;
+  let bin = 0b1010 == 10;
+  let oct = 0o17 == 15;
+  let zero = 0x0 == 0;
+  return hex + bin + oct + zero;
+}
+
+function total(n) {
+  return n + 0xff + 0B1010 + 0O17;
+}
+
+matches() * 1000 + total(0);
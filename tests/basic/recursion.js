@@ -0,0 +1,23 @@
+// Self-recursion, and an even/odd pair that call each other before and after
+// being defined. Tail calls compile to return_call unless --no-tail-calls is given.
+function fact(n) {
+  if (n <= 1) return 1;
+  return n * fact(n - 1);
+}
+
+function factTail(n, acc) {
+  if (n <= 1) return acc;
+  return factTail(n - 1, acc * n);
+}
+
+function isEven(n) {
+  if (n == 0) return 1;
+  return isOdd(n - 1);
+}
+
+function isOdd(n) {
+  if (n == 0) return 0;
+  return isEven(n - 1);
+}
+
+fact(5) * 1000 + factTail(4, 1) * 10 + isEven(10) + isOdd(7);
@@ -0,0 +1,14 @@
+// Logical expressions in return position leave exactly one value for `return`
+function and_ret(a, b) {
+  return a && b;
+}
+
+function seven() {
+  return 7;
+}
+
+function or_ret(a) {
+  return a || seven();
+}
+
+and_ret(3, 4) * 1000 + and_ret(0, 9) * 100 + or_ret(0) * 10 + or_ret(2);
@@ -0,0 +1,18 @@
+// An inner x shadows the outer one, and the outer value is back after the block
+function inner(n) {
+  let x = n;
+  let seen = 0;
+  {
+    let x = n * 10;
+    seen = x;
+  }
+  return seen + x;
+}
+
+let x = 2.5;
+{
+  let x = 3;
+  x = x + 1;
+}
+let r = x - x;   // the outer x: f32, so r is f32 too
+inner(4) + r;
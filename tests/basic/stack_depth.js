@@ -0,0 +1,7 @@
+// Stack depth report (--report-stack-depth): a + (b + (c + (d + a)))
+// pushes all five operands before the first add, so deep peaks at 5
+function deep(a, b, c, d) {
+  return a + (b + (c + (d + a)));
+}
+
+deep(1, 2, 3, 4);
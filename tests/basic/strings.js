@@ -0,0 +1,10 @@
+// String literals live in a data segment; print passes address and length to the host
+function greet(name) {
+  print(name);
+  return 0;
+}
+
+let s = "hello";
+greet(s);
+print("tab\there");
+0;
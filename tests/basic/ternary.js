@@ -0,0 +1,15 @@
+// Ternary conditional: result-typed if, right-associative, truthy conditions
+function max(a, b) {
+  let m = a > b ? a : b;
+  return m;
+}
+
+function sign(x) {
+  return x > 0 ? 1 : x < 0 ? -1 : 0;
+}
+
+function pick(n) {
+  return n % 3 ? 10 : 20;
+}
+
+max(4, 9) * 100 + (sign(-5) + 2) * 10 + pick(3);
@@ -0,0 +1,2 @@
+// Token dump (--emit=tokens): each token with its line and column
+let x = 1;
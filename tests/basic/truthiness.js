@@ -0,0 +1,44 @@
+// Test i32 truthiness: conditions on computed values, not just comparisons
+function truthiness(x) {
+  let result = 0;
+
+  // Nonzero parameter is truthy
+  if (x) {
+    result = result + 1;
+  } else {
+    result = result + 100;
+  }
+
+  // Computed zero is falsy
+  if (x - 5) {
+    result = result + 100;
+  } else {
+    result = result + 2;
+  }
+
+  // Negative values are truthy
+  if (x * -1) {
+    result = result + 4;
+  }
+
+  // while (n) runs until n reaches 0
+  let n = x;
+  while (n) {
+    n = n - 1;
+    result = result + 10;
+  }
+
+  // Constant conditions (folded by the optimizer)
+  if (5) {
+    result = result + 200;
+  }
+  if (0) {
+    result = result + 1000;
+  } else {
+    result = result + 300;
+  }
+
+  return result;
+}
+
+truthiness(5);
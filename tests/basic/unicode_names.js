@@ -0,0 +1,7 @@
+// Non-ASCII names are escaped in WAT ids but exported unchanged
+function größe(maß) {
+  let ñ = maß * 2;
+  return ñ;
+}
+
+größe(21);
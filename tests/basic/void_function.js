@@ -0,0 +1,15 @@
+// A function without a return has no result, and its call is not dropped
+function bump(n) {
+  n = n + 1;
+}
+
+function twice(x) {
+  if (x > 0) {
+    return x * 2;
+  } else {
+    return 0;
+  }
+}
+
+bump(1);
+twice(21);
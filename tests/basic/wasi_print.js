@@ -0,0 +1,5 @@
+// With --wasi, print writes a line to stdout through fd_write
+print(123);
+print(-45);
+print("done");
+0;
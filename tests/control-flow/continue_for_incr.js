@@ -0,0 +1,13 @@
+// Continue in for loop must still run the increment
+function continue_for_incr() {
+  let sum = 0;
+  for (let i = 0; i < 20; i = i + 1) {
+    if (i < 15) {
+      continue;
+    }
+    sum = sum + i;
+  }
+  return sum;
+}
+
+continue_for_incr();
@@ -0,0 +1,52 @@
+// Test if conditions with && / || lowered to direct branches
+function short_circuit_if(a, b, zero) {
+  let result = 0;
+
+  // && with else: both operands must be truthy
+  if (a && b) {
+    result = result + 1;
+  } else {
+    result = result + 100;
+  }
+  if (a && zero) {
+    result = result + 100;
+  } else {
+    result = result + 2;
+  }
+
+  // || without else
+  if (zero || b) {
+    result = result + 4;
+  }
+  if (zero || zero) {
+    result = result + 100;
+  }
+
+  // Nested and negated operands
+  if (a && (zero || !zero)) {
+    result = result + 8;
+  }
+  if (!(a || b) || zero) {
+    result = result + 100;
+  } else {
+    result = result + 16;
+  }
+
+  // The right operand is skipped: a / zero would trap
+  if (zero && a / zero) {
+    result = result + 100;
+  }
+  if (a || a / zero) {
+    result = result + 32;
+  }
+
+  // f32 operands
+  let f = 0.5;
+  if (f && a) {
+    result = result + 64;
+  }
+
+  return result;
+}
+
+short_circuit_if(3, 4, 0);
@@ -0,0 +1,38 @@
+// switch: fall-through without break, break, default, and continue inside a loop
+function grade(n) {
+  let points = 0;
+  switch (n) {
+    case 1:
+      points = points + 1;
+    case 2:
+      points = points + 10;
+      break;
+    case 3:
+      points = 100;
+      break;
+    default:
+      points = 1000;
+  }
+  return points;
+}
+
+function sign(x) {
+  switch (x > 0) {
+    case 1:
+      return 1;
+    default:
+      return 0 - 1;
+  }
+}
+
+let total = 0;
+for (let i = 0; i < 5; i = i + 1) {
+  switch (i) {
+    case 0:
+      continue;
+    case 4.0:
+      total = total + 5000;
+  }
+  total = total + 1;
+}
+grade(1) + grade(2) + grade(3) + grade(7) + sign(5) + sign(0) + total;
@@ -0,0 +1,3 @@
+// Assigning to a const is rejected inside an expression too
+const limit = 10;
+let copy = (limit = 20);
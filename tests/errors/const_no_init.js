@@ -0,0 +1,7 @@
+// Error: a const must be given its value where it is declared
+function limit() {
+  const c;
+  return 1;
+}
+
+limit();
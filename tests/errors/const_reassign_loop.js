@@ -0,0 +1,10 @@
+// Error: a const reassigned inside a loop is rejected before codegen, not a panic
+function const_reassign_loop() {
+  const limit = 3;
+  for (let i = 0; i < 5; i++) {
+    limit = i;
+  }
+  return limit;
+}
+
+const_reassign_loop();
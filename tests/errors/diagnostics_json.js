@@ -0,0 +1,3 @@
+// One warning and one error, reported together
+let bits = 1 << 33;
+let total = count + bits;
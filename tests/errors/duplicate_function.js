@@ -0,0 +1,10 @@
+// Error: the same function name is defined twice
+function f() {
+  return 1;
+}
+
+function f() {
+  return 2;
+}
+
+f();
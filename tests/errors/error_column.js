@@ -0,0 +1,5 @@
+// The error points at the second token on line 3, after the indentation
+function lives() {
+  let 9lives = 1;
+  return 0;
+}
@@ -0,0 +1,5 @@
+// A tab-indented line: the caret repeats the tab so it stays under the token
+function total() {
+	let sum = 1 +;
+	return sum;
+}
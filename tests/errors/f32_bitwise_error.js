@@ -0,0 +1,6 @@
+// Error: bitwise operators only apply to i32
+function mask(a) {
+  return a & 3;
+}
+
+mask(2.5);
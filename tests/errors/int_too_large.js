@@ -0,0 +1,7 @@
+// Error: integer literal does not fit in i32
+function too_large() {
+  let big = 123456789012;
+  return big;
+}
+
+too_large();
@@ -0,0 +1,7 @@
+// Error: Z is not a hex digit
+function mask() {
+  let m = 0xZZ;
+  return m;
+}
+
+mask();
@@ -0,0 +1,7 @@
+// Error: loop keywords are reserved and cannot name a variable
+function loop_keyword() {
+  let for = 1;
+  return for;
+}
+
+loop_keyword();
@@ -0,0 +1,11 @@
+// Error: a return inside a loop does not cover the path where the loop ends
+function find(n) {
+  while (n > 0) {
+    if (n % 7 == 0) {
+      return n;
+    }
+    n = n - 1;
+  }
+}
+
+find(20);
@@ -0,0 +1 @@
+let = 5; // Error: declaration without a variable name
@@ -0,0 +1,11 @@
+// Error: sign(0) falls off the end without a value
+function sign(x) {
+  if (x > 0) {
+    return 1;
+  }
+  if (x < 0) {
+    return -1;
+  }
+}
+
+sign(5);
@@ -0,0 +1,12 @@
+// Two independent syntax errors; both are reported
+function first() {
+  let a = ;
+  return 1;
+}
+
+function second() {
+  let b = 2
+  return b;
+}
+
+first() + second();
@@ -0,0 +1,15 @@
+// Error: the arity mismatch on h is reported with its enclosing calls
+function h(a) {
+  return a;
+}
+
+function g(a) {
+  return a + 1;
+}
+
+function f(a) {
+  return a * 2;
+}
+
+h(1);
+f(g(h(1, 2)));
@@ -0,0 +1,5 @@
+// null cannot be used as a number
+function next() {
+  let x = null;
+  return x + 1;
+}
@@ -0,0 +1,6 @@
+// Error: 'class' is a reserved word, so it cannot name a parameter
+function area(class) {
+  return class * 2;
+}
+
+area(3);
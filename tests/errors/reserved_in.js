@@ -0,0 +1,7 @@
+// Error: 'in' is a reserved word that is not supported
+function reserved_in(key, table) {
+  let found = key in table;
+  return found;
+}
+
+reserved_in(1, 2);
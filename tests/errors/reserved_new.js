@@ -0,0 +1,7 @@
+// Error: 'new' is a reserved word that is not supported
+function reserved_new() {
+  let p = new Point(1, 2);
+  return p;
+}
+
+reserved_new();
@@ -0,0 +1,6 @@
+// Error: 'typeof' is a reserved word that is not supported
+function reserved_typeof(x) {
+  return typeof x;
+}
+
+reserved_typeof(1);
@@ -0,0 +1,3 @@
+// Error: strings have no arithmetic
+let s = "abc";
+let n = s + 1;
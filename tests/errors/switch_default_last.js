@@ -0,0 +1,11 @@
+// Error: a case after 'default' is not supported; 'default' must come last
+function pick(n) {
+  switch (n) {
+    default:
+      return 0;
+    case 1:
+      return 1;
+  }
+}
+
+pick(1);
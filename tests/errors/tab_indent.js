@@ -0,0 +1,7 @@
+// Error (with --no-tabs): tab-indented source is rejected
+function tabs() {
+	let x = 4;
+	return x + 1;
+}
+
+tabs();
@@ -0,0 +1,6 @@
+// Error: first call passes fewer arguments than declared
+function add(a, b) {
+  return a + b;
+}
+
+add(1);
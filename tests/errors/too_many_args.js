@@ -0,0 +1,6 @@
+// Error: call passes more arguments than declared
+function square(x) {
+  return x * x;
+}
+
+square(3, 4);
@@ -0,0 +1,7 @@
+// Error: the returned name was never declared, so no WAT is written
+function undefined_in_return() {
+  let total = 1;
+  return totl;
+}
+
+undefined_in_return();
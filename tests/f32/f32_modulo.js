@@ -0,0 +1,16 @@
+// `%` matches JS for both types: the result keeps the sign of the left operand.
+// In JS, -7 % 3 is -1, 5.5 % 2.0 is 1.5 and -5.5 % 2.0 is -1.5.
+function imod(a, b) {
+  return a % b;
+}
+
+function fmod(a, b) {
+  return a % b;
+}
+
+// Folded at compile time, with the same rule
+let ifolded = -7 % 3;
+let ffolded = 5.5 % 2.0;
+
+// 150 - 15 - 1, plus two terms that are 0 when the folds agree
+fmod(5.5, 2.0) * 100 + fmod(-5.5, 2.0) * 10 + imod(-7, 3) + (ffolded - 1.5) + (ifolded + 1);
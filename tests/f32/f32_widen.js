@@ -0,0 +1,10 @@
+// i32 operands mixed with f32 are widened, so every local and the result are f32
+function f32_widen() {
+  let a = 4 + 0.5;
+  let b = 7 - 2.25;
+  let c = 3 * 0.5;
+  let total = a + b + c;
+  return total;
+}
+
+f32_widen();
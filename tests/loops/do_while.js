@@ -0,0 +1,32 @@
+// The body runs before the condition is first tested; continue jumps to the test
+function once(flag) {
+  let runs = 0;
+  do {
+    runs = runs + 1;
+  } while (flag);
+  return runs;
+}
+
+function skip_two(n) {
+  let i = 0;
+  let sum = 0;
+  do {
+    i = i + 1;
+    if (i == 2) {
+      continue;
+    }
+    if (i > 4) {
+      break;
+    }
+    sum = sum + i;
+  } while (i < n);
+  return sum;
+}
+
+// while (0) leaves just the body
+function folded(x) {
+  do x = x * 2; while (0);
+  return x;
+}
+
+once(0) + skip_two(10) + folded(3);
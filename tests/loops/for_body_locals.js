@@ -0,0 +1,11 @@
+// Locals declared in a for initializer and body both get (local ...) declarations
+function for_body_locals() {
+  let last = 0;
+  for (let i = 0; i < 3; i = i + 1) {
+    let j = i;
+    last = last + j;
+  }
+  return last;
+}
+
+for_body_locals();
@@ -0,0 +1,13 @@
+// For loops stepping with i++ and --j
+function for_loop_increment() {
+  let sum = 0;
+  for (let i = 0; i < 5; i++) {
+    sum = sum + i * i;
+  }
+  for (let j = 3; j > 0; --j) {
+    sum = sum + j;
+  }
+  return sum;
+}
+
+for_loop_increment();
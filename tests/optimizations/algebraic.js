@@ -0,0 +1,19 @@
+// Identities with one literal operand simplify away; x * 0 keeps a call
+function count() {
+  return 5;
+}
+
+function identities(x, f) {
+  let a = x + 0;
+  let b = 0 + x;
+  let c = x - 0;
+  let d = x * 1;
+  let e = 1 * x;
+  let g = x / 1;
+  let z = x * 0;
+  let keep = count() * 0;
+  let h = f * 1.0 - 0.0;
+  return a + b + c + d + e + g + z + keep + h;
+}
+
+identities(7, 0.5);
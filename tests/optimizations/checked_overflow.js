@@ -0,0 +1,6 @@
+// 65536 * 65536 wraps to 0 by default and traps under --checked-arithmetic
+function grow(a, b) {
+  return a * b;
+}
+
+grow(65536, 65536);
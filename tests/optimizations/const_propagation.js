@@ -0,0 +1,19 @@
+// Literal consts and never-reassigned lets are substituted, then folded
+function twice() {
+  const n = 5;
+  return n + n;
+}
+
+function scaled() {
+  let k = 3;
+  const limit = 2.5;
+  return k * 4 + limit;
+}
+
+function counter() {
+  let c = 1;
+  c = c + 2;
+  return c * 4;
+}
+
+twice() + scaled() + counter();
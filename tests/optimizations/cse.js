@@ -0,0 +1,16 @@
+function square_sum(a, b) {
+    return (a + b) * (a + b);
+}
+
+function shared_branches(a, b, s) {
+    let t = 0;
+    if (s > 0) t = (a * b + 1) - (a * b + 1) / 2;
+    else t = a - b;
+    return t;
+}
+
+function updated(a) {
+    return (a + 1) * (a++ + 1);
+}
+
+square_sum(3, 4) + shared_branches(5, 6, 1) + updated(2);
@@ -0,0 +1,21 @@
+// Bindings that are never used are dropped unless computing them calls a function
+function bump() {
+  return 5;
+}
+
+function unused() {
+  let unused = 3;
+  return 1;
+}
+
+function used(n) {
+  let used = n * 3;
+  return used;
+}
+
+function effect() {
+  let ignored = bump();
+  return 2;
+}
+
+unused() + used(2) + effect();
@@ -0,0 +1,154 @@
+// Forty levels of nested if, while and block statements. Each level adds one
+// to total when n is deep enough, so deep(100) reaches the bottom and returns 40
+function deep(n) {
+  let total = 0;
+  if (n > 0) {
+    total = total + 1;
+    let once1 = 0;
+    while (once1 < 1 && n > 1) {
+      once1 = once1 + 1;
+      total = total + 1;
+      {
+        total = total + 1;
+        if (n > 3) {
+          total = total + 1;
+          let once4 = 0;
+          while (once4 < 1 && n > 4) {
+            once4 = once4 + 1;
+            total = total + 1;
+            {
+              total = total + 1;
+              if (n > 6) {
+                total = total + 1;
+                let once7 = 0;
+                while (once7 < 1 && n > 7) {
+                  once7 = once7 + 1;
+                  total = total + 1;
+                  {
+                    total = total + 1;
+                    if (n > 9) {
+                      total = total + 1;
+                      let once10 = 0;
+                      while (once10 < 1 && n > 10) {
+                        once10 = once10 + 1;
+                        total = total + 1;
+                        {
+                          total = total + 1;
+                          if (n > 12) {
+                            total = total + 1;
+                            let once13 = 0;
+                            while (once13 < 1 && n > 13) {
+                              once13 = once13 + 1;
+                              total = total + 1;
+                              {
+                                total = total + 1;
+                                if (n > 15) {
+                                  total = total + 1;
+                                  let once16 = 0;
+                                  while (once16 < 1 && n > 16) {
+                                    once16 = once16 + 1;
+                                    total = total + 1;
+                                    {
+                                      total = total + 1;
+                                      if (n > 18) {
+                                        total = total + 1;
+                                        let once19 = 0;
+                                        while (once19 < 1 && n > 19) {
+                                          once19 = once19 + 1;
+                                          total = total + 1;
+                                          {
+                                            total = total + 1;
+                                            if (n > 21) {
+                                              total = total + 1;
+                                              let once22 = 0;
+                                              while (once22 < 1 && n > 22) {
+                                                once22 = once22 + 1;
+                                                total = total + 1;
+                                                {
+                                                  total = total + 1;
+                                                  if (n > 24) {
+                                                    total = total + 1;
+                                                    let once25 = 0;
+                                                    while (once25 < 1 && n > 25) {
+                                                      once25 = once25 + 1;
+                                                      total = total + 1;
+                                                      {
+                                                        total = total + 1;
+                                                        if (n > 27) {
+                                                          total = total + 1;
+                                                          let once28 = 0;
+                                                          while (once28 < 1 && n > 28) {
+                                                            once28 = once28 + 1;
+                                                            total = total + 1;
+                                                            {
+                                                              total = total + 1;
+                                                              if (n > 30) {
+                                                                total = total + 1;
+                                                                let once31 = 0;
+                                                                while (once31 < 1 && n > 31) {
+                                                                  once31 = once31 + 1;
+                                                                  total = total + 1;
+                                                                  {
+                                                                    total = total + 1;
+                                                                    if (n > 33) {
+                                                                      total = total + 1;
+                                                                      let once34 = 0;
+                                                                      while (once34 < 1 && n > 34) {
+                                                                        once34 = once34 + 1;
+                                                                        total = total + 1;
+                                                                        {
+                                                                          total = total + 1;
+                                                                          if (n > 36) {
+                                                                            total = total + 1;
+                                                                            let once37 = 0;
+                                                                            while (once37 < 1 && n > 37) {
+                                                                              once37 = once37 + 1;
+                                                                              total = total + 1;
+                                                                              {
+                                                                                total = total + 1;
+                                                                                if (n > 39) {
+                                                                                  total = total + 1;
+                                                                                }
+                                                                              }
+                                                                            }
+                                                                          }
+                                                                        }
+                                                                      }
+                                                                    }
+                                                                  }
+                                                                }
+                                                              }
+                                                            }
+                                                          }
+                                                        }
+                                                      }
+                                                    }
+                                                  }
+                                                }
+                                              }
+                                            }
+                                          }
+                                        }
+                                      }
+                                    }
+                                  }
+                                }
+                              }
+                            }
+                          }
+                        }
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+  return total;
+}
+
+deep(100);
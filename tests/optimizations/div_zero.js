@@ -0,0 +1,12 @@
+// Constant division by zero is left for the runtime (i32.div_s traps) instead of folded
+function div_zero(n) {
+  if (n < 0) {
+    return 10 / 0;
+  }
+  if (n > 100) {
+    return 10 % 0;
+  }
+  return n + 1;
+}
+
+div_zero(4);
@@ -0,0 +1,15 @@
+function scale(x) {
+    let y = x * 3;
+    return y + 1;
+}
+
+function triple_plus_one(x) {
+    let y = x * 3;
+    return y + 1;
+}
+
+function sum_both(x) {
+    return scale(x) + triple_plus_one(x);
+}
+
+sum_both(4);
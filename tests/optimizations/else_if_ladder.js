@@ -0,0 +1,23 @@
+// else-if ladders with constant conditions fold down to the one live branch
+function ladder(x) {
+  let r = 0;
+  if (0) {
+    r = 111;
+  } else if (0.0) {
+    r = 222;
+  } else if (2) {
+    r = x * 10;
+  } else {
+    r = 444;
+  }
+  return r;
+}
+
+function braceless() {
+  if (0) return 111;
+  else if (0) return 222;
+  else if (1) return 3;
+  else return 444;
+}
+
+ladder(7) + braceless();
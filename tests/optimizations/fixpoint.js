@@ -0,0 +1,11 @@
+// Needs two optimizer passes: removing the dead branch in the first pass leaves
+// flag never reassigned, so the second propagates it and folds flag * 10
+function scaled() {
+  let flag = 1;
+  if (0) {
+    flag = 2;
+  }
+  return flag * 10;
+}
+
+scaled();
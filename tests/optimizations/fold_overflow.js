@@ -0,0 +1,9 @@
+// Folding wraps like i32.add instead of overflowing the compiler
+function fold_overflow() {
+  let max = 2147483647 + 1;
+  let min = -(-2147483647 - 1);
+  let big = 65536 * 65536;
+  return max + min + big + 5;
+}
+
+fold_overflow();
@@ -0,0 +1,32 @@
+function accumulate(a, b, n) {
+    let i = 0;
+    let x = 0;
+    let total = 0;
+    while (i < n) {
+        x = a * b + i;
+        total = total + x;
+        i = i + 1;
+    }
+    return total;
+}
+
+function nested(a, n) {
+    let sum = 0;
+    for (let i = 0; i < n; i++) {
+        for (let j = 0; j < n; j++) {
+            sum = sum + (a + 1) * i + j;
+        }
+    }
+    return sum;
+}
+
+function changing(a, n) {
+    let sum = 0;
+    for (let i = 0; i < n; i++) {
+        sum = sum + a * 3;
+        a = a + 1;
+    }
+    return sum;
+}
+
+accumulate(3, 4, 5) + nested(2, 3) + changing(1, 3);
@@ -0,0 +1,26 @@
+// A constant left operand decides && and ||; the other operand is dropped
+function bump() {
+  return 100;
+}
+
+function and_zero(x) {
+  return 0 && bump();
+}
+
+function and_one(x) {
+  return 1 && x;
+}
+
+function or_one(x) {
+  return 1 || bump();
+}
+
+function or_zero(x) {
+  return 0 || x + 1;
+}
+
+function widened() {
+  return 0 && 2.5;
+}
+
+and_zero(7) + and_one(7) + or_one(7) + or_zero(7) + widened();
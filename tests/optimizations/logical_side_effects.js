@@ -0,0 +1,18 @@
+// Folding && and || keeps JavaScript's evaluation: a call that short-circuiting
+// skips never runs, so it is dropped, but a call whose value is not needed still runs
+function note(n) {
+  print(n);
+  return n;
+}
+
+function zero(x) {
+  return x && 0;
+}
+
+let a = 1 || note(1);
+let b = note(2) && 0;
+let c = note(3) || 0;
+let d = 0 && note(4);
+
+// 1 + 0 + 3 * 10 + 0 * 100 + 0 * 1000
+a + b + c * 10 + d * 100 + zero(5) * 1000;
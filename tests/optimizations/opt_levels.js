@@ -0,0 +1,11 @@
+// -O0 lowers as written, -O1 folds constants, -O2 also propagates them
+function sum() {
+  return 1 + 2;
+}
+
+function twice() {
+  let a = 4;
+  return a * 3;
+}
+
+sum() + twice();
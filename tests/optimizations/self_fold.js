@@ -0,0 +1,9 @@
+function self_fold(a) {
+    const k = 4;
+    let d = a - a;
+    let q = k / k;
+    let r = a / a;
+    return d + q + r;
+}
+
+self_fold(7);
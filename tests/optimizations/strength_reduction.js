@@ -0,0 +1,14 @@
+function scale(x) {
+    return x * 8;
+}
+
+function quarter(x) {
+    return x / 4;
+}
+
+function scaled_expr(a, b) {
+    return 16 * (a + b);
+}
+
+// -7 / 4 rounds toward zero to -1, where -7 >> 2 would give -2
+scale(5) * 100 + quarter(-7) * 10 + quarter(9) + scaled_expr(1, 2);
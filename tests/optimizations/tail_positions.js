@@ -0,0 +1,18 @@
+// Only a call that is the whole returned value becomes return_call: one in sum
+// and one in mixed. The other returns in mixed call double and then do more work.
+function sum(n, acc) {
+  if (n == 0) return acc;
+  return sum(n - 1, acc + n);
+}
+
+function double(x) {
+  return x * 2;
+}
+
+function mixed(n) {
+  if (n > 10) return double(n);
+  if (n > 5) return 1 + double(n);
+  return double(n) + double(1);
+}
+
+sum(10, 0) + mixed(20) + mixed(7) + mixed(2);
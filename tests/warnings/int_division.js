@@ -0,0 +1,8 @@
+// Warning with --warn int-division: 7 / 2 truncates to 3; 8 / 2 is exact
+function halves() {
+  let a = 7 / 2;
+  let b = 8 / 2;
+  return a + b;
+}
+
+halves();
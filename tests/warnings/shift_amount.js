@@ -0,0 +1,6 @@
+// Warning: WASM masks shift amounts to 5 bits, so `x << 33` shifts by 1
+function double(x) {
+  return x << 33;
+}
+
+double(4);
@@ -0,0 +1,10 @@
+// _start keeps every top-level value of its result type, so `5;` is returned
+// and does not warn, though a `let` follows it. Inside `fade`, `2 * 3;` warns.
+function fade() {
+  2 * 3;
+  return 1;
+}
+
+fade();
+5;
+let y = 2;
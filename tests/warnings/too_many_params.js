@@ -0,0 +1,10 @@
+// Warning: sum9 has 9 parameters (more than 8); sum8 is at the limit
+function sum8(a, b, c, d, e, f, g, h) {
+  return a + b + c + d + e + f + g + h;
+}
+
+function sum9(a, b, c, d, e, f, g, h, i) {
+  return sum8(a, b, c, d, e, f, g, h) + i;
+}
+
+sum9(1, 2, 3, 4, 5, 6, 7, 8, 9);
@@ -0,0 +1,13 @@
+// Warning: `1 + 2;` discards its value; `bump();` and the final result do not warn
+function bump() {
+  return 1;
+}
+
+function unused() {
+  1 + 2;
+  bump();
+  return 3;
+}
+
+unused();
+unused() + 4;
@@ -0,0 +1,11 @@
+// Warning with --warn unused-variable: tmp is never read; total and n are
+function sum(n) {
+  let total = 0;
+  let tmp = n * 2;
+  for (let i = 1; i <= n; i = i + 1) {
+    total = total + i;
+  }
+  return total;
+}
+
+sum(10);